@@ -10,6 +10,8 @@ fn main() {
     {
         bridge
             .include("recastnavigation/Detour/Include")
+            // Make the dtQueryFilter methods virtual, so they can be implemented in Rust
+            .define("DT_VIRTUAL_QUERYFILTER", None)
             // Detour source files
             .file("recastnavigation/Detour/Source/DetourAlloc.cpp")
             .file("recastnavigation/Detour/Source/DetourAssert.cpp")
@@ -21,6 +23,10 @@ fn main() {
             // Our additional functions
             .file("src/detour.cpp");
 
+        // Use 64 bit polygon and tile references
+        #[cfg(feature = "polyref64")]
+        bridge.define("DT_POLYREF64", None);
+
         #[cfg(feature = "detour_crowd")]
         {
             bridge
@@ -33,8 +39,7 @@ fn main() {
                 .file("recastnavigation/DetourCrowd/Source/DetourPathQueue.cpp")
                 .file("recastnavigation/DetourCrowd/Source/DetourProximityGrid.cpp")
                 // Our additional functions
-                //.file("src/detour_crowd.cpp")
-                ;
+                .file("src/detour_crowd.cpp");
         }
     }
     #[cfg(feature = "recast")]
@@ -9,9 +9,73 @@
 #include "recast-sys/recastnavigation/Detour/Include/DetourStatus.h"
 
 struct NavMeshCreateParams;
+struct NavMeshParams;
+struct MeshTileHeader;
+struct MeshTileLink;
+struct RaycastHitData;
+struct RandomSource;
+struct PolyQueryCallback;
+struct RustQueryFilterCallbacks;
+
+/// Query filter delegating polygon filtering and cost computation to Rust callbacks.
+class RustQueryFilter : public dtQueryFilter {
+    public:
+    RustQueryFilter(rust::Box<RustQueryFilterCallbacks> callbacks);
+    ~RustQueryFilter();
+
+    bool passFilter(const dtPolyRef ref, const dtMeshTile* tile, const dtPoly* poly) const override;
+    float getCost(const float* pa, const float* pb,
+                  const dtPolyRef prevRef, const dtMeshTile* prevTile, const dtPoly* prevPoly,
+                  const dtPolyRef curRef, const dtMeshTile* curTile, const dtPoly* curPoly,
+                  const dtPolyRef nextRef, const dtMeshTile* nextTile, const dtPoly* nextPoly) const override;
+
+    private:
+    rust::Box<RustQueryFilterCallbacks> m_callbacks;
+};
 
 std::unique_ptr<dtNavMesh> newDtNavMesh();
 std::unique_ptr<dtNavMeshQuery> newDtNavMeshQuery();
 std::unique_ptr<dtQueryFilter> newDtQueryFilter();
+std::unique_ptr<RustQueryFilter> newRustQueryFilter(rust::Box<RustQueryFilterCallbacks> callbacks);
+const dtQueryFilter& rustQueryFilterAsBase(RustQueryFilter const& filter);
 
 bool createNavMeshData(NavMeshCreateParams* params, std::uint8_t **outData, std::int32_t *outDataSize);
+
+std::uint32_t navMeshInitWithParams(dtNavMesh& navmesh, NavMeshParams const& params);
+NavMeshParams navMeshGetParams(dtNavMesh const& navmesh);
+std::uint32_t navMeshRestoreTileState(dtNavMesh& navmesh, dtTileRef tileRef,
+                                      const std::uint8_t* data, std::int32_t maxDataSize);
+
+std::uint32_t navMeshQueryRaycast(dtNavMeshQuery const& query, dtPolyRef startRef,
+                                  const float* startPos, const float* endPos,
+                                  const dtQueryFilter* filter, std::uint32_t options,
+                                  dtPolyRef prevRef, dtPolyRef* path, std::int32_t maxPath,
+                                  RaycastHitData& hit);
+
+std::uint32_t navMeshQueryFindRandomPoint(dtNavMeshQuery const& query, const dtQueryFilter* filter,
+                                          RandomSource& rng, dtPolyRef* randomRef, float* randomPt);
+std::uint32_t navMeshQueryFindRandomPointAroundCircle(dtNavMeshQuery const& query, dtPolyRef startRef,
+                                                      const float* centerPos, const float maxRadius,
+                                                      const dtQueryFilter* filter, RandomSource& rng,
+                                                      dtPolyRef* randomRef, float* randomPt);
+
+std::uint32_t navMeshQueryQueryPolygons(dtNavMeshQuery const& query, const float* center,
+                                        const float* halfExtents, const dtQueryFilter* filter,
+                                        PolyQueryCallback& callback);
+
+bool meshTileHasHeader(dtMeshTile const& tile);
+MeshTileHeader meshTileGetHeader(dtMeshTile const& tile);
+const std::uint8_t* meshTileGetData(dtMeshTile const& tile);
+std::int32_t meshTileGetDataSize(dtMeshTile const& tile);
+const float* meshTileGetVerts(dtMeshTile const& tile);
+const dtPoly& meshTileGetPoly(dtMeshTile const& tile, std::int32_t index);
+MeshTileLink meshTileGetLink(dtMeshTile const& tile, std::uint32_t index);
+
+std::uint8_t* allocNavMeshData(std::int32_t size);
+void freeNavMeshData(std::uint8_t* data);
+std::uint32_t checkNavMeshData(rust::Slice<const std::uint8_t> data);
+
+std::uint16_t polyGetFlags(dtPoly const& poly);
+std::uint8_t polyGetVertCount(dtPoly const& poly);
+const std::uint16_t* polyGetVerts(dtPoly const& poly);
+std::uint32_t polyGetFirstLink(dtPoly const& poly);
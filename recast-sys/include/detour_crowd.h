@@ -1,10 +1,30 @@
 #pragma once
 
 #include "rust/cxx.h"
+#include <array>
+#include <cstdint>
 #include <memory>
 
+#include "recast-sys/recastnavigation/DetourCrowd/Include/DetourCrowd.h"
 #include "recast-sys/recastnavigation/DetourCrowd/Include/DetourPathCorridor.h"
+#include "recast-sys/recastnavigation/DetourCrowd/Include/DetourPathQueue.h"
+#include "recast-sys/recastnavigation/DetourCrowd/Include/DetourProximityGrid.h"
 
 struct NavMeshCreateParams;
+struct CrowdAgentParams;
+struct CrowdAgentInfo;
+struct ObstacleAvoidanceConfig;
 
-std::unique_ptr<dtPathCorridor> newDtPathCorridor();
\ No newline at end of file
+std::unique_ptr<dtPathCorridor> newDtPathCorridor();
+
+std::unique_ptr<dtCrowd> newDtCrowd();
+std::unique_ptr<dtPathQueue> newDtPathQueue();
+std::unique_ptr<dtProximityGrid> newDtProximityGrid();
+std::int32_t crowdAddAgent(dtCrowd& crowd, std::array<float, 3> const& position, CrowdAgentParams const& params);
+void crowdUpdateAgentParameters(dtCrowd& crowd, std::int32_t index, CrowdAgentParams const& params);
+bool crowdGetAgentInfo(dtCrowd const& crowd, std::int32_t index, CrowdAgentInfo& info);
+void crowdUpdate(dtCrowd& crowd, float dt);
+bool crowdSetObstacleAvoidanceParams(dtCrowd& crowd, std::int32_t index, ObstacleAvoidanceConfig const& params);
+bool crowdGetObstacleAvoidanceParams(dtCrowd const& crowd, std::int32_t index, ObstacleAvoidanceConfig& params);
+bool crowdSetQueryFilter(dtCrowd& crowd, std::int32_t index, dtQueryFilter const& filter);
+bool pathCorridorIsValid(dtPathCorridor const& corridor, std::int32_t maxLookAhead, dtNavMeshQuery const& query, dtQueryFilter const* filter);
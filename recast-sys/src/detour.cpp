@@ -1,4 +1,5 @@
 #include "recast-sys/include/detour.h"
+#include <cstring>
 
 #include "recast-sys/src/lib.rs.h"
 
@@ -14,6 +15,32 @@ std::unique_ptr<dtQueryFilter> newDtQueryFilter() {
     return std::make_unique<dtQueryFilter>();
 }
 
+RustQueryFilter::RustQueryFilter(rust::Box<RustQueryFilterCallbacks> callbacks): m_callbacks(std::move(callbacks)) { }
+
+RustQueryFilter::~RustQueryFilter() = default;
+
+bool RustQueryFilter::passFilter(const dtPolyRef ref, const dtMeshTile* tile, const dtPoly* poly) const {
+    return m_callbacks->pass_filter(ref, *tile, *poly);
+}
+
+float RustQueryFilter::getCost(const float* pa, const float* pb,
+                               const dtPolyRef prevRef, const dtMeshTile* prevTile, const dtPoly* prevPoly,
+                               const dtPolyRef curRef, const dtMeshTile* curTile, const dtPoly* curPoly,
+                               const dtPolyRef nextRef, const dtMeshTile* nextTile, const dtPoly* nextPoly) const {
+    return m_callbacks->get_cost(pa, pb,
+                                 prevRef, prevTile, prevPoly,
+                                 curRef, curTile, curPoly,
+                                 nextRef, nextTile, nextPoly);
+}
+
+std::unique_ptr<RustQueryFilter> newRustQueryFilter(rust::Box<RustQueryFilterCallbacks> callbacks) {
+    return std::make_unique<RustQueryFilter>(std::move(callbacks));
+}
+
+const dtQueryFilter& rustQueryFilterAsBase(RustQueryFilter const& filter) {
+    return filter;
+}
+
 std::unique_ptr<dtPathCorridor> newDtPathCorridor() {
     return std::make_unique<dtPathCorridor>();
 }
@@ -54,3 +81,232 @@ bool createNavMeshData(NavMeshCreateParams* params, std::uint8_t **outData, std:
 
     return dtCreateNavMeshData(&dtParams, outData, outDataSize);
 }
+
+std::uint32_t navMeshInitWithParams(dtNavMesh& navmesh, NavMeshParams const& params) {
+    auto dtParams = dtNavMeshParams();
+    std::copy(params.origin.begin(), params.origin.end(), dtParams.orig);
+    dtParams.tileWidth = params.tile_width;
+    dtParams.tileHeight = params.tile_height;
+    dtParams.maxTiles = params.max_tiles;
+    dtParams.maxPolys = params.max_polys;
+
+    return navmesh.init(&dtParams);
+}
+
+NavMeshParams navMeshGetParams(dtNavMesh const& navmesh) {
+    auto dtParams = navmesh.getParams();
+    auto params = NavMeshParams();
+    std::copy(dtParams->orig, dtParams->orig + 3, params.origin.begin());
+    params.tile_width = dtParams->tileWidth;
+    params.tile_height = dtParams->tileHeight;
+    params.max_tiles = dtParams->maxTiles;
+    params.max_polys = dtParams->maxPolys;
+
+    return params;
+}
+
+std::uint32_t navMeshRestoreTileState(dtNavMesh& navmesh, dtTileRef tileRef,
+                                      const std::uint8_t* data, std::int32_t maxDataSize) {
+    auto tile = navmesh.getTileByRef(tileRef);
+    if (!tile) {
+        return DT_FAILURE | DT_INVALID_PARAM;
+    }
+    // Detour only exposes const tiles, but restoring the state only modifies the polygons
+    return navmesh.restoreTileState(const_cast<dtMeshTile*>(tile), data, maxDataSize);
+}
+
+std::uint32_t navMeshQueryRaycast(dtNavMeshQuery const& query, dtPolyRef startRef,
+                                  const float* startPos, const float* endPos,
+                                  const dtQueryFilter* filter, std::uint32_t options,
+                                  dtPolyRef prevRef, dtPolyRef* path, std::int32_t maxPath,
+                                  RaycastHitData& hit) {
+    auto dtHit = dtRaycastHit();
+    dtHit.path = path;
+    dtHit.maxPath = maxPath;
+    auto status = query.raycast(startRef, startPos, endPos, filter, options, &dtHit, prevRef);
+
+    hit.t = dtHit.t;
+    std::copy(dtHit.hitNormal, dtHit.hitNormal + 3, hit.hit_normal.begin());
+    hit.hit_edge_index = dtHit.hitEdgeIndex;
+    hit.path_count = dtHit.pathCount;
+    hit.path_cost = dtHit.pathCost;
+
+    return status;
+}
+
+namespace {
+    // Detour takes a plain function pointer as its random number generator, so the Rust random
+    // source is passed to it through a thread local.
+    thread_local RandomSource* currentRandomSource = nullptr;
+
+    float randomSourceTrampoline() {
+        return currentRandomSource->next_f32();
+    }
+
+    /// Sets the random source of the current thread for the lifetime of the object.
+    class RandomSourceScope {
+        public:
+        RandomSourceScope(RandomSource& rng): m_previous(currentRandomSource) {
+            currentRandomSource = &rng;
+        }
+
+        ~RandomSourceScope() {
+            currentRandomSource = m_previous;
+        }
+
+        private:
+        RandomSource* m_previous;
+    };
+}
+
+std::uint32_t navMeshQueryFindRandomPoint(dtNavMeshQuery const& query, const dtQueryFilter* filter,
+                                          RandomSource& rng, dtPolyRef* randomRef, float* randomPt) {
+    RandomSourceScope scope(rng);
+    return query.findRandomPoint(filter, randomSourceTrampoline, randomRef, randomPt);
+}
+
+std::uint32_t navMeshQueryFindRandomPointAroundCircle(dtNavMeshQuery const& query, dtPolyRef startRef,
+                                                      const float* centerPos, const float maxRadius,
+                                                      const dtQueryFilter* filter, RandomSource& rng,
+                                                      dtPolyRef* randomRef, float* randomPt) {
+    RandomSourceScope scope(rng);
+    return query.findRandomPointAroundCircle(startRef, centerPos, maxRadius, filter,
+                                             randomSourceTrampoline, randomRef, randomPt);
+}
+
+namespace {
+    /// Forwards the polygons found by dtNavMeshQuery::queryPolygons to a Rust callback.
+    class RustPolyQuery : public dtPolyQuery {
+        public:
+        RustPolyQuery(PolyQueryCallback& callback): m_callback(callback) { }
+
+        void process(const dtMeshTile*, dtPoly**, dtPolyRef* refs, int count) override {
+            m_callback.process(rust::Slice<const dtPolyRef>(refs, count));
+        }
+
+        private:
+        PolyQueryCallback& m_callback;
+    };
+}
+
+std::uint32_t navMeshQueryQueryPolygons(dtNavMeshQuery const& query, const float* center,
+                                        const float* halfExtents, const dtQueryFilter* filter,
+                                        PolyQueryCallback& callback) {
+    RustPolyQuery polyQuery(callback);
+    return query.queryPolygons(center, halfExtents, filter, &polyQuery);
+}
+
+bool meshTileHasHeader(dtMeshTile const& tile) {
+    return tile.header != nullptr;
+}
+
+MeshTileHeader meshTileGetHeader(dtMeshTile const& tile) {
+    auto header = MeshTileHeader();
+    header.x = tile.header->x;
+    header.y = tile.header->y;
+    header.layer = tile.header->layer;
+    header.user_id = tile.header->userId;
+    header.poly_count = tile.header->polyCount;
+    header.vert_count = tile.header->vertCount;
+    header.max_link_count = tile.header->maxLinkCount;
+    header.off_mesh_con_count = tile.header->offMeshConCount;
+    header.walkable_height = tile.header->walkableHeight;
+    header.walkable_radius = tile.header->walkableRadius;
+    header.walkable_climb = tile.header->walkableClimb;
+    std::copy(tile.header->bmin, tile.header->bmin + 3, header.b_min.begin());
+    std::copy(tile.header->bmax, tile.header->bmax + 3, header.b_max.begin());
+
+    return header;
+}
+
+const std::uint8_t* meshTileGetData(dtMeshTile const& tile) {
+    return tile.data;
+}
+
+std::int32_t meshTileGetDataSize(dtMeshTile const& tile) {
+    return tile.dataSize;
+}
+
+const float* meshTileGetVerts(dtMeshTile const& tile) {
+    return tile.verts;
+}
+
+const dtPoly& meshTileGetPoly(dtMeshTile const& tile, std::int32_t index) {
+    return tile.polys[index];
+}
+
+MeshTileLink meshTileGetLink(dtMeshTile const& tile, std::uint32_t index) {
+    const dtLink& link = tile.links[index];
+    auto result = MeshTileLink();
+    result.poly_ref = link.ref;
+    result.next = link.next;
+    result.edge = link.edge;
+    result.side = link.side;
+    result.b_min = link.bmin;
+    result.b_max = link.bmax;
+    return result;
+}
+
+std::uint8_t* allocNavMeshData(std::int32_t size) {
+    return static_cast<std::uint8_t*>(dtAlloc(size, DT_ALLOC_PERM));
+}
+
+void freeNavMeshData(std::uint8_t* data) {
+    dtFree(data);
+}
+
+std::uint32_t checkNavMeshData(rust::Slice<const std::uint8_t> data) {
+    if (data.size() < sizeof(dtMeshHeader)) {
+        return DT_FAILURE | DT_INVALID_PARAM;
+    }
+    dtMeshHeader header;
+    std::memcpy(&header, data.data(), sizeof(dtMeshHeader));
+    if (header.magic != DT_NAVMESH_MAGIC) {
+        return DT_FAILURE | DT_WRONG_MAGIC;
+    }
+    if (header.version != DT_NAVMESH_VERSION) {
+        return DT_FAILURE | DT_WRONG_VERSION;
+    }
+
+    const int counts[] = {
+        header.vertCount, header.polyCount, header.maxLinkCount, header.detailMeshCount,
+        header.detailVertCount, header.detailTriCount, header.bvNodeCount, header.offMeshConCount,
+    };
+    for (int count : counts) {
+        if (count < 0) {
+            return DT_FAILURE | DT_INVALID_PARAM;
+        }
+    }
+
+    // Same layout as the one expected by dtNavMesh::addTile
+    auto align4 = [](std::uint64_t x) { return (x + 3) & ~std::uint64_t(3); };
+    const std::uint64_t size = align4(sizeof(dtMeshHeader))
+        + align4(sizeof(float) * 3 * std::uint64_t(header.vertCount))
+        + align4(sizeof(dtPoly) * std::uint64_t(header.polyCount))
+        + align4(sizeof(dtLink) * std::uint64_t(header.maxLinkCount))
+        + align4(sizeof(dtPolyDetail) * std::uint64_t(header.detailMeshCount))
+        + align4(sizeof(float) * 3 * std::uint64_t(header.detailVertCount))
+        + align4(sizeof(std::uint8_t) * 4 * std::uint64_t(header.detailTriCount))
+        + align4(sizeof(dtBVNode) * std::uint64_t(header.bvNodeCount))
+        + align4(sizeof(dtOffMeshConnection) * std::uint64_t(header.offMeshConCount));
+    if (size != data.size()) {
+        return DT_FAILURE | DT_INVALID_PARAM;
+    }
+    return DT_SUCCESS;
+}
+
+std::uint16_t polyGetFlags(dtPoly const& poly) {
+    return poly.flags;
+}
+
+std::uint8_t polyGetVertCount(dtPoly const& poly) {
+    return poly.vertCount;
+}
+
+const std::uint16_t* polyGetVerts(dtPoly const& poly) {
+    return poly.verts;
+}
+
+std::uint32_t polyGetFirstLink(dtPoly const& poly) {
+    return poly.firstLink;
+}
@@ -0,0 +1,134 @@
+#include "recast-sys/include/detour_crowd.h"
+
+#include "recast-sys/src/lib.rs.h"
+
+#include <algorithm>
+
+std::unique_ptr<dtCrowd> newDtCrowd() {
+    return std::make_unique<dtCrowd>();
+}
+
+std::unique_ptr<dtPathQueue> newDtPathQueue() {
+    return std::make_unique<dtPathQueue>();
+}
+
+std::unique_ptr<dtProximityGrid> newDtProximityGrid() {
+    return std::make_unique<dtProximityGrid>();
+}
+
+static dtCrowdAgentParams toDtAgentParams(CrowdAgentParams const& params) {
+    auto dtParams = dtCrowdAgentParams();
+    dtParams.radius = params.radius;
+    dtParams.height = params.height;
+    dtParams.maxAcceleration = params.max_acceleration;
+    dtParams.maxSpeed = params.max_speed;
+    dtParams.collisionQueryRange = params.collision_query_range;
+    dtParams.pathOptimizationRange = params.path_optimization_range;
+    dtParams.separationWeight = params.separation_weight;
+    dtParams.updateFlags = params.update_flags;
+    dtParams.obstacleAvoidanceType = params.obstacle_avoidance_type;
+    dtParams.queryFilterType = params.query_filter_type;
+    dtParams.userData = nullptr;
+    return dtParams;
+}
+
+static CrowdAgentParams fromDtAgentParams(dtCrowdAgentParams const& dtParams) {
+    auto params = CrowdAgentParams();
+    params.radius = dtParams.radius;
+    params.height = dtParams.height;
+    params.max_acceleration = dtParams.maxAcceleration;
+    params.max_speed = dtParams.maxSpeed;
+    params.collision_query_range = dtParams.collisionQueryRange;
+    params.path_optimization_range = dtParams.pathOptimizationRange;
+    params.separation_weight = dtParams.separationWeight;
+    params.update_flags = dtParams.updateFlags;
+    params.obstacle_avoidance_type = dtParams.obstacleAvoidanceType;
+    params.query_filter_type = dtParams.queryFilterType;
+    return params;
+}
+
+std::int32_t crowdAddAgent(dtCrowd& crowd, std::array<float, 3> const& position, CrowdAgentParams const& params) {
+    auto dtParams = toDtAgentParams(params);
+    return crowd.addAgent(position.data(), &dtParams);
+}
+
+void crowdUpdateAgentParameters(dtCrowd& crowd, std::int32_t index, CrowdAgentParams const& params) {
+    auto dtParams = toDtAgentParams(params);
+    crowd.updateAgentParameters(index, &dtParams);
+}
+
+bool crowdGetAgentInfo(dtCrowd const& crowd, std::int32_t index, CrowdAgentInfo& info) {
+    // getAgent is not const, even though it does not modify the crowd
+    auto agent = const_cast<dtCrowd&>(crowd).getAgent(index);
+    if (!agent) {
+        return false;
+    }
+    info.active = agent->active;
+    info.state = agent->state;
+    info.partial = agent->partial;
+    std::copy(agent->npos, agent->npos + 3, info.position.begin());
+    std::copy(agent->vel, agent->vel + 3, info.velocity.begin());
+    std::copy(agent->dvel, agent->dvel + 3, info.desired_velocity.begin());
+    info.desired_speed = agent->desiredSpeed;
+    info.target_state = agent->targetState;
+    info.target_ref = agent->targetRef;
+    std::copy(agent->targetPos, agent->targetPos + 3, info.target_position.begin());
+    auto corridorTarget = agent->corridor.getTarget();
+    std::copy(corridorTarget, corridorTarget + 3, info.corridor_target.begin());
+    info.params = fromDtAgentParams(agent->params);
+    return true;
+}
+
+void crowdUpdate(dtCrowd& crowd, float dt) {
+    crowd.update(dt, nullptr);
+}
+
+bool crowdSetObstacleAvoidanceParams(dtCrowd& crowd, std::int32_t index, ObstacleAvoidanceConfig const& params) {
+    if (index < 0 || index >= DT_CROWD_MAX_OBSTAVOIDANCE_PARAMS) {
+        return false;
+    }
+    auto dtParams = dtObstacleAvoidanceParams();
+    dtParams.velBias = params.vel_bias;
+    dtParams.weightDesVel = params.weight_des_vel;
+    dtParams.weightCurVel = params.weight_cur_vel;
+    dtParams.weightSide = params.weight_side;
+    dtParams.weightToi = params.weight_toi;
+    dtParams.horizTime = params.horiz_time;
+    dtParams.gridSize = params.grid_size;
+    dtParams.adaptiveDivs = params.adaptive_divs;
+    dtParams.adaptiveRings = params.adaptive_rings;
+    dtParams.adaptiveDepth = params.adaptive_depth;
+    crowd.setObstacleAvoidanceParams(index, &dtParams);
+    return true;
+}
+
+bool crowdGetObstacleAvoidanceParams(dtCrowd const& crowd, std::int32_t index, ObstacleAvoidanceConfig& params) {
+    auto dtParams = crowd.getObstacleAvoidanceParams(index);
+    if (!dtParams) {
+        return false;
+    }
+    params.vel_bias = dtParams->velBias;
+    params.weight_des_vel = dtParams->weightDesVel;
+    params.weight_cur_vel = dtParams->weightCurVel;
+    params.weight_side = dtParams->weightSide;
+    params.weight_toi = dtParams->weightToi;
+    params.horiz_time = dtParams->horizTime;
+    params.grid_size = dtParams->gridSize;
+    params.adaptive_divs = dtParams->adaptiveDivs;
+    params.adaptive_rings = dtParams->adaptiveRings;
+    params.adaptive_depth = dtParams->adaptiveDepth;
+    return true;
+}
+
+bool crowdSetQueryFilter(dtCrowd& crowd, std::int32_t index, dtQueryFilter const& filter) {
+    auto crowdFilter = crowd.getEditableFilter(index);
+    if (!crowdFilter) {
+        return false;
+    }
+    *crowdFilter = filter;
+    return true;
+}
+
+bool pathCorridorIsValid(dtPathCorridor const& corridor, std::int32_t maxLookAhead, dtNavMeshQuery const& query, dtQueryFilter const* filter) {
+    return const_cast<dtPathCorridor&>(corridor).isValid(maxLookAhead, const_cast<dtNavMeshQuery*>(&query), filter);
+}
@@ -77,6 +77,9 @@ impl Default for RecastConfig {
 // TODO: safety preconditions will need to be documented before release
 #[allow(clippy::too_many_arguments, clippy::missing_safety_doc)]
 pub mod ffi {
+    #[cfg(feature = "detour")]
+    use super::{PolyQueryCallback, RandomSource, RustQueryFilterCallbacks};
+
     #[cxx::bridge]
     #[cfg(feature = "recast")]
     pub mod recast {
@@ -182,6 +185,17 @@ pub mod ffi {
                 areas: *mut u8,
             );
 
+            #[rust_name = "clear_unwalkable_triangles"]
+            pub unsafe fn rcClearUnwalkableTriangles(
+                context: *mut rcContext,
+                walkable_slope_angle: f32,
+                vertices: *const f32,
+                n_vertices: i32,
+                indices: *const i32,
+                n_triangles: i32,
+                areas: *mut u8,
+            );
+
             #[rust_name = "rasterize_triangles_with_indices"]
             pub unsafe fn rcRasterizeTriangles(
                 context: *mut rcContext,
@@ -392,6 +406,106 @@ pub mod ffi {
             build_bv_tree: bool,
         }
 
+        /// Parameters of a (possibly multi-tile) navmesh, equivalent to `dtNavMeshParams`.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct NavMeshParams {
+            origin: [f32; 3],
+            tile_width: f32,
+            tile_height: f32,
+            max_tiles: i32,
+            max_polys: i32,
+        }
+
+        /// Result of a navmesh raycast, equivalent to `dtRaycastHit` minus the path buffer.
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
+        struct RaycastHitData {
+            t: f32,
+            hit_normal: [f32; 3],
+            hit_edge_index: i32,
+            path_count: i32,
+            path_cost: f32,
+        }
+
+        /// Header of a navmesh tile, containing the most useful fields of `dtMeshHeader`.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct MeshTileHeader {
+            x: i32,
+            y: i32,
+            layer: i32,
+            user_id: u32,
+            poly_count: i32,
+            vert_count: i32,
+            max_link_count: i32,
+            off_mesh_con_count: i32,
+            walkable_height: f32,
+            walkable_radius: f32,
+            walkable_climb: f32,
+            b_min: [f32; 3],
+            b_max: [f32; 3],
+        }
+
+        /// Link between a navmesh polygon and one of its neighbours, equivalent to `dtLink`.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct MeshTileLink {
+            poly_ref: dtPolyRef,
+            next: u32,
+            edge: u8,
+            side: u8,
+            b_min: u8,
+            b_max: u8,
+        }
+
+        /// Configuration of a crowd agent, equivalent to `dtCrowdAgentParams` minus the user data.
+        #[cfg(feature = "detour_crowd")]
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct CrowdAgentParams {
+            radius: f32,
+            height: f32,
+            max_acceleration: f32,
+            max_speed: f32,
+            collision_query_range: f32,
+            path_optimization_range: f32,
+            separation_weight: f32,
+            update_flags: u8,
+            obstacle_avoidance_type: u8,
+            query_filter_type: u8,
+        }
+
+        /// State of a crowd agent, containing the most useful fields of `dtCrowdAgent`.
+        #[cfg(feature = "detour_crowd")]
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct CrowdAgentInfo {
+            active: bool,
+            state: u8,
+            partial: bool,
+            position: [f32; 3],
+            velocity: [f32; 3],
+            desired_velocity: [f32; 3],
+            desired_speed: f32,
+            target_state: u8,
+            target_ref: dtPolyRef,
+            target_position: [f32; 3],
+            corridor_target: [f32; 3],
+            params: CrowdAgentParams,
+        }
+
+        /// Configuration of the obstacle avoidance of crowd agents, equivalent to
+        /// `dtObstacleAvoidanceParams`.
+        #[cfg(feature = "detour_crowd")]
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct ObstacleAvoidanceConfig {
+            vel_bias: f32,
+            weight_des_vel: f32,
+            weight_cur_vel: f32,
+            weight_side: f32,
+            weight_toi: f32,
+            horiz_time: f32,
+            grid_size: u8,
+            adaptive_divs: u8,
+            adaptive_rings: u8,
+            adaptive_depth: u8,
+        }
+
         #[repr(i32)]
         enum dtTileFlags {
             #[rust_name = "FreeData"]
@@ -406,21 +520,72 @@ pub mod ffi {
             DT_STRAIGHTPATH_ALL_CROSSINGS = 2,
         }
 
+        extern "Rust" {
+            type RandomSource<'a>;
+
+            fn next_f32(self: &mut RandomSource<'_>) -> f32;
+
+            type PolyQueryCallback<'a>;
+
+            fn process(self: &mut PolyQueryCallback<'_>, polys: &[dtPolyRef]);
+
+            type RustQueryFilterCallbacks;
+
+            fn pass_filter(
+                self: &RustQueryFilterCallbacks,
+                poly_ref: dtPolyRef,
+                tile: &dtMeshTile,
+                poly: &dtPoly,
+            ) -> bool;
+
+            unsafe fn get_cost(
+                self: &RustQueryFilterCallbacks,
+                pa: *const f32,
+                pb: *const f32,
+                prev_ref: dtPolyRef,
+                prev_tile: *const dtMeshTile,
+                prev_poly: *const dtPoly,
+                cur_ref: dtPolyRef,
+                cur_tile: *const dtMeshTile,
+                cur_poly: *const dtPoly,
+                next_ref: dtPolyRef,
+                next_tile: *const dtMeshTile,
+                next_poly: *const dtPoly,
+            ) -> f32;
+        }
+
         unsafe extern "C++" {
             include!("recast-sys/include/detour.h");
 
             #[cfg(feature = "detour_crowd")]
             include!("recast-sys/include/detour_crowd.h");
 
+            type dtPolyRef = crate::dtPolyRef;
+            type dtTileRef = crate::dtTileRef;
             type dtNavMesh;
+            type dtMeshTile;
+            type dtPoly;
             type dtNavMeshQuery;
             type dtTileFlags;
             type dtStraightPathOptions;
             type dtQueryFilter;
 
+            /// `dtQueryFilter` subclass delegating its methods to a [`QueryFilterCallbacks`](crate::QueryFilterCallbacks)
+            /// implementation.
+            type RustQueryFilter;
+
             #[cfg(feature = "detour_crowd")]
             type dtPathCorridor;
 
+            #[cfg(feature = "detour_crowd")]
+            type dtCrowd;
+
+            #[cfg(feature = "detour_crowd")]
+            type dtPathQueue;
+
+            #[cfg(feature = "detour_crowd")]
+            type dtProximityGrid;
+
             #[rust_name = "new_navmesh"]
             pub fn newDtNavMesh() -> UniquePtr<dtNavMesh>;
 
@@ -430,10 +595,30 @@ pub mod ffi {
             #[rust_name = "new_query_filter"]
             pub fn newDtQueryFilter() -> UniquePtr<dtQueryFilter>;
 
+            #[rust_name = "new_rust_query_filter"]
+            pub fn newRustQueryFilter(
+                callbacks: Box<RustQueryFilterCallbacks>,
+            ) -> UniquePtr<RustQueryFilter>;
+
+            #[rust_name = "rust_query_filter_as_base"]
+            pub fn rustQueryFilterAsBase(filter: &RustQueryFilter) -> &dtQueryFilter;
+
             #[rust_name = "new_path_corridor"]
             #[cfg(feature = "detour_crowd")]
             pub fn newDtPathCorridor() -> UniquePtr<dtPathCorridor>;
 
+            #[rust_name = "new_crowd"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn newDtCrowd() -> UniquePtr<dtCrowd>;
+
+            #[rust_name = "new_path_queue"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn newDtPathQueue() -> UniquePtr<dtPathQueue>;
+
+            #[rust_name = "new_proximity_grid"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn newDtProximityGrid() -> UniquePtr<dtProximityGrid>;
+
             #[rust_name = "create_navmesh_data"]
             pub unsafe fn createNavMeshData(
                 params: *mut NavMeshCreateParams,
@@ -449,6 +634,159 @@ pub mod ffi {
                 flags: i32,
             ) -> u32;
 
+            #[rust_name = "init_with_params"]
+            pub fn navMeshInitWithParams(navmesh: Pin<&mut dtNavMesh>, params: &NavMeshParams) -> u32;
+
+            #[rust_name = "navmesh_get_params"]
+            pub fn navMeshGetParams(navmesh: &dtNavMesh) -> NavMeshParams;
+
+            #[rust_name = "add_tile"]
+            pub unsafe fn addTile(
+                self: Pin<&mut dtNavMesh>,
+                data: *mut u8,
+                data_size: i32,
+                flags: i32,
+                last_ref: dtTileRef,
+                result: *mut dtTileRef,
+            ) -> u32;
+
+            #[rust_name = "get_max_tiles"]
+            pub fn getMaxTiles(self: &dtNavMesh) -> i32;
+
+            #[rust_name = "get_tile"]
+            pub unsafe fn getTile(self: &dtNavMesh, i: i32) -> *const dtMeshTile;
+
+            #[rust_name = "get_tile_ref"]
+            pub unsafe fn getTileRef(self: &dtNavMesh, tile: *const dtMeshTile) -> dtTileRef;
+
+            #[rust_name = "get_tile_by_ref"]
+            pub fn getTileByRef(self: &dtNavMesh, tile_ref: dtTileRef) -> *const dtMeshTile;
+
+            #[rust_name = "get_tile_state_size"]
+            pub unsafe fn getTileStateSize(self: &dtNavMesh, tile: *const dtMeshTile) -> i32;
+
+            #[rust_name = "store_tile_state"]
+            pub unsafe fn storeTileState(
+                self: &dtNavMesh,
+                tile: *const dtMeshTile,
+                data: *mut u8,
+                max_data_size: i32,
+            ) -> u32;
+
+            #[rust_name = "restore_tile_state"]
+            /// Restore the state of the tile referred to by `tile_ref`, or fail with an invalid
+            /// parameter status if the reference is not valid.
+            pub unsafe fn navMeshRestoreTileState(
+                navmesh: Pin<&mut dtNavMesh>,
+                tile_ref: dtTileRef,
+                data: *const u8,
+                max_data_size: i32,
+            ) -> u32;
+
+            #[rust_name = "get_poly_ref_base"]
+            pub unsafe fn getPolyRefBase(self: &dtNavMesh, tile: *const dtMeshTile) -> dtPolyRef;
+
+            #[rust_name = "set_poly_flags"]
+            pub fn setPolyFlags(self: Pin<&mut dtNavMesh>, poly_ref: dtPolyRef, flags: u16) -> u32;
+
+            #[rust_name = "get_poly_flags"]
+            pub unsafe fn getPolyFlags(
+                self: &dtNavMesh,
+                poly_ref: dtPolyRef,
+                result_flags: *mut u16,
+            ) -> u32;
+
+            #[rust_name = "set_poly_area"]
+            pub fn setPolyArea(self: Pin<&mut dtNavMesh>, poly_ref: dtPolyRef, area: u8) -> u32;
+
+            #[rust_name = "get_poly_area"]
+            pub unsafe fn getPolyArea(
+                self: &dtNavMesh,
+                poly_ref: dtPolyRef,
+                result_area: *mut u8,
+            ) -> u32;
+
+            #[rust_name = "is_valid_poly_ref"]
+            pub fn isValidPolyRef(self: &dtNavMesh, poly_ref: dtPolyRef) -> bool;
+
+            #[rust_name = "decode_poly_id"]
+            pub fn decodePolyId(
+                self: &dtNavMesh,
+                poly_ref: dtPolyRef,
+                salt: &mut u32,
+                tile: &mut u32,
+                poly: &mut u32,
+            );
+
+            #[rust_name = "mesh_tile_has_header"]
+            pub fn meshTileHasHeader(tile: &dtMeshTile) -> bool;
+
+            #[rust_name = "mesh_tile_get_header"]
+            /// # Safety
+            ///
+            /// The tile must have a header, see `mesh_tile_has_header`.
+            pub unsafe fn meshTileGetHeader(tile: &dtMeshTile) -> MeshTileHeader;
+
+            #[rust_name = "mesh_tile_get_data"]
+            pub fn meshTileGetData(tile: &dtMeshTile) -> *const u8;
+
+            #[rust_name = "mesh_tile_get_data_size"]
+            pub fn meshTileGetDataSize(tile: &dtMeshTile) -> i32;
+
+            #[rust_name = "mesh_tile_get_verts"]
+            pub fn meshTileGetVerts(tile: &dtMeshTile) -> *const f32;
+
+            #[rust_name = "mesh_tile_get_poly"]
+            /// # Safety
+            ///
+            /// The index must be lower than the polygon count of the tile.
+            pub unsafe fn meshTileGetPoly(tile: &dtMeshTile, index: i32) -> &dtPoly;
+
+            #[rust_name = "mesh_tile_get_link"]
+            /// # Safety
+            ///
+            /// The index must be a valid link index of the tile, obtained from `poly_get_first_link`
+            /// or from the `next` field of another link.
+            pub unsafe fn meshTileGetLink(tile: &dtMeshTile, index: u32) -> MeshTileLink;
+
+            #[rust_name = "poly_get_area"]
+            pub fn getArea(self: &dtPoly) -> u8;
+
+            #[rust_name = "poly_get_type"]
+            pub fn getType(self: &dtPoly) -> u8;
+
+            #[rust_name = "poly_get_flags"]
+            pub fn polyGetFlags(poly: &dtPoly) -> u16;
+
+            #[rust_name = "poly_get_vertex_count"]
+            pub fn polyGetVertCount(poly: &dtPoly) -> u8;
+
+            #[rust_name = "poly_get_verts"]
+            pub fn polyGetVerts(poly: &dtPoly) -> *const u16;
+
+            #[rust_name = "poly_get_first_link"]
+            pub fn polyGetFirstLink(poly: &dtPoly) -> u32;
+
+            #[rust_name = "alloc_navmesh_data"]
+            /// Allocate a buffer for navmesh tile data using the Detour allocator, so that it can be
+            /// passed to a navmesh with the `DT_TILE_FREE_DATA` flag.
+            pub fn allocNavMeshData(size: i32) -> *mut u8;
+
+            #[rust_name = "free_navmesh_data"]
+            /// Free a buffer allocated by `alloc_navmesh_data`.
+            ///
+            /// # Safety
+            ///
+            /// The pointer passed to this method must have been obtained by the
+            /// `alloc_navmesh_data` function and must not have been already free'd.
+            pub unsafe fn freeNavMeshData(data: *mut u8);
+
+            #[rust_name = "check_navmesh_data"]
+            /// Check that `data` starts with a `dtMeshHeader` of the current magic and version,
+            /// and that its size matches the element counts of the header, returning a
+            /// `dtStatus`.
+            pub fn checkNavMeshData(data: &[u8]) -> u32;
+
             #[rust_name = "init"]
             pub unsafe fn init(
                 self: Pin<&mut dtNavMeshQuery>,
@@ -459,7 +797,7 @@ pub mod ffi {
             #[rust_name = "closest_point_on_poly"]
             pub unsafe fn closestPointOnPoly(
                 self: &dtNavMeshQuery,
-                ref_: u32,
+                ref_: dtPolyRef,
                 pos: *const f32,
                 closest: *mut f32,
                 pos_over_poly: *mut bool,
@@ -471,46 +809,238 @@ pub mod ffi {
                 center: *const f32,
                 half_extents: *const f32,
                 filter: *const dtQueryFilter,
-                nearest_ref: *mut u32,
+                nearest_ref: *mut dtPolyRef,
                 nearest_point: *mut f32,
             ) -> u32;
 
             #[rust_name = "find_path"]
             pub unsafe fn findPath(
                 self: &dtNavMeshQuery,
-                start_poly: u32,
-                end_poly: u32,
+                start_poly: dtPolyRef,
+                end_poly: dtPolyRef,
+                origin: *const f32,
+                destination: *const f32,
+                filter: *const dtQueryFilter,
+                path: *mut dtPolyRef,
+                path_len: *mut i32,
+                max_len: i32,
+            ) -> u32;
+
+            #[rust_name = "init_sliced_find_path"]
+            pub unsafe fn initSlicedFindPath(
+                self: Pin<&mut dtNavMeshQuery>,
+                start_poly: dtPolyRef,
+                end_poly: dtPolyRef,
                 origin: *const f32,
                 destination: *const f32,
                 filter: *const dtQueryFilter,
-                path: *mut u32,
+                options: u32,
+            ) -> u32;
+
+            #[rust_name = "update_sliced_find_path"]
+            pub unsafe fn updateSlicedFindPath(
+                self: Pin<&mut dtNavMeshQuery>,
+                max_iters: i32,
+                done_iters: *mut i32,
+            ) -> u32;
+
+            #[rust_name = "finalize_sliced_find_path"]
+            pub unsafe fn finalizeSlicedFindPath(
+                self: Pin<&mut dtNavMeshQuery>,
+                path: *mut dtPolyRef,
                 path_len: *mut i32,
                 max_len: i32,
             ) -> u32;
 
+            #[rust_name = "finalize_sliced_find_path_partial"]
+            pub unsafe fn finalizeSlicedFindPathPartial(
+                self: Pin<&mut dtNavMeshQuery>,
+                existing: *const dtPolyRef,
+                existing_len: i32,
+                path: *mut dtPolyRef,
+                path_len: *mut i32,
+                max_len: i32,
+            ) -> u32;
+
+            #[rust_name = "raycast"]
+            pub unsafe fn navMeshQueryRaycast(
+                query: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                start_pos: *const f32,
+                end_pos: *const f32,
+                filter: *const dtQueryFilter,
+                options: u32,
+                prev_poly: dtPolyRef,
+                path: *mut dtPolyRef,
+                max_path: i32,
+                hit: &mut RaycastHitData,
+            ) -> u32;
+
             #[rust_name = "find_straight_path"]
             pub unsafe fn findStraightPath(
                 self: &dtNavMeshQuery,
                 start_pos: *const f32,
                 end_pos: *const f32,
-                path: *const u32,
+                path: *const dtPolyRef,
                 path_len: i32,
                 straight_path: *mut f32,
                 straight_path_flags: *mut u8,
-                straight_path_polys: *mut u32,
+                straight_path_polys: *mut dtPolyRef,
                 straight_path_len: *mut i32,
                 max_len: i32,
                 options: i32,
             ) -> u32;
 
+            #[rust_name = "move_along_surface"]
+            pub unsafe fn moveAlongSurface(
+                self: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                start_pos: *const f32,
+                end_pos: *const f32,
+                filter: *const dtQueryFilter,
+                result_pos: *mut f32,
+                visited: *mut dtPolyRef,
+                visited_len: *mut i32,
+                max_visited: i32,
+            ) -> u32;
+
+            #[rust_name = "find_distance_to_wall"]
+            pub unsafe fn findDistanceToWall(
+                self: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                center_pos: *const f32,
+                max_radius: f32,
+                filter: *const dtQueryFilter,
+                hit_dist: *mut f32,
+                hit_pos: *mut f32,
+                hit_normal: *mut f32,
+            ) -> u32;
+
+            #[rust_name = "get_poly_wall_segments"]
+            pub unsafe fn getPolyWallSegments(
+                self: &dtNavMeshQuery,
+                poly: dtPolyRef,
+                filter: *const dtQueryFilter,
+                segment_verts: *mut f32,
+                segment_refs: *mut dtPolyRef,
+                segment_count: *mut i32,
+                max_segments: i32,
+            ) -> u32;
+
+            #[rust_name = "find_random_point"]
+            pub unsafe fn navMeshQueryFindRandomPoint(
+                query: &dtNavMeshQuery,
+                filter: *const dtQueryFilter,
+                rng: &mut RandomSource,
+                random_ref: *mut dtPolyRef,
+                random_pt: *mut f32,
+            ) -> u32;
+
+            #[rust_name = "find_random_point_around_circle"]
+            pub unsafe fn navMeshQueryFindRandomPointAroundCircle(
+                query: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                center_pos: *const f32,
+                max_radius: f32,
+                filter: *const dtQueryFilter,
+                rng: &mut RandomSource,
+                random_ref: *mut dtPolyRef,
+                random_pt: *mut f32,
+            ) -> u32;
+
+            #[rust_name = "find_polys_around_circle"]
+            pub unsafe fn findPolysAroundCircle(
+                self: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                center_pos: *const f32,
+                radius: f32,
+                filter: *const dtQueryFilter,
+                result_ref: *mut dtPolyRef,
+                result_parent: *mut dtPolyRef,
+                result_cost: *mut f32,
+                result_count: *mut i32,
+                max_result: i32,
+            ) -> u32;
+
+            #[rust_name = "find_polys_around_shape"]
+            pub unsafe fn findPolysAroundShape(
+                self: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                verts: *const f32,
+                nverts: i32,
+                filter: *const dtQueryFilter,
+                result_ref: *mut dtPolyRef,
+                result_parent: *mut dtPolyRef,
+                result_cost: *mut f32,
+                result_count: *mut i32,
+                max_result: i32,
+            ) -> u32;
+
+            #[rust_name = "query_polygons"]
+            pub unsafe fn queryPolygons(
+                self: &dtNavMeshQuery,
+                center: *const f32,
+                half_extents: *const f32,
+                filter: *const dtQueryFilter,
+                polys: *mut dtPolyRef,
+                poly_count: *mut i32,
+                max_polys: i32,
+            ) -> u32;
+
+            #[rust_name = "query_polygons_callback"]
+            pub unsafe fn navMeshQueryQueryPolygons(
+                query: &dtNavMeshQuery,
+                center: *const f32,
+                half_extents: *const f32,
+                filter: *const dtQueryFilter,
+                callback: &mut PolyQueryCallback,
+            ) -> u32;
+
+            #[rust_name = "find_local_neighbourhood"]
+            pub unsafe fn findLocalNeighbourhood(
+                self: &dtNavMeshQuery,
+                start_poly: dtPolyRef,
+                center_pos: *const f32,
+                radius: f32,
+                filter: *const dtQueryFilter,
+                result_ref: *mut dtPolyRef,
+                result_parent: *mut dtPolyRef,
+                result_count: *mut i32,
+                max_result: i32,
+            ) -> u32;
+
             #[rust_name = "get_poly_height"]
             pub unsafe fn getPolyHeight(
                 self: &dtNavMeshQuery,
-                poly: u32,
+                poly: dtPolyRef,
                 pos: *const f32,
                 height: *mut f32,
             ) -> u32;
 
+            #[rust_name = "get_include_flags"]
+            pub fn getIncludeFlags(self: &dtQueryFilter) -> u16;
+
+            #[rust_name = "set_include_flags"]
+            pub fn setIncludeFlags(self: Pin<&mut dtQueryFilter>, flags: u16);
+
+            #[rust_name = "get_exclude_flags"]
+            pub fn getExcludeFlags(self: &dtQueryFilter) -> u16;
+
+            #[rust_name = "set_exclude_flags"]
+            pub fn setExcludeFlags(self: Pin<&mut dtQueryFilter>, flags: u16);
+
+            #[rust_name = "get_area_cost"]
+            /// # Safety
+            ///
+            /// `area` must be lower than `DT_MAX_AREAS`.
+            pub unsafe fn getAreaCost(self: &dtQueryFilter, area: i32) -> f32;
+
+            #[rust_name = "set_area_cost"]
+            /// # Safety
+            ///
+            /// `area` must be lower than `DT_MAX_AREAS`.
+            pub unsafe fn setAreaCost(self: Pin<&mut dtQueryFilter>, area: i32, cost: f32);
+
             #[rust_name = "init"]
             #[cfg(feature = "detour_crowd")]
             pub unsafe fn init(self: Pin<&mut dtPathCorridor>, max_len: i32) -> bool;
@@ -526,14 +1056,14 @@ pub mod ffi {
 
             #[rust_name = "reset"]
             #[cfg(feature = "detour_crowd")]
-            pub unsafe fn reset(self: Pin<&mut dtPathCorridor>, poly: u32, pos: *const f32);
+            pub unsafe fn reset(self: Pin<&mut dtPathCorridor>, poly: dtPolyRef, pos: *const f32);
 
             #[rust_name = "set_corridor"]
             #[cfg(feature = "detour_crowd")]
             pub unsafe fn setCorridor(
                 self: Pin<&mut dtPathCorridor>,
                 target: *const f32,
-                path: *const u32,
+                path: *const dtPolyRef,
                 path_len: i32,
             );
 
@@ -543,7 +1073,7 @@ pub mod ffi {
                 self: Pin<&mut dtPathCorridor>,
                 corner_vertices: *mut f32,
                 corner_flags: *mut u8,
-                corner_polys: *mut u32,
+                corner_polys: *mut dtPolyRef,
                 max_len: i32,
                 query: *mut dtNavMeshQuery,
                 filter: *const dtQueryFilter,
@@ -557,10 +1087,405 @@ pub mod ffi {
                 query: *mut dtNavMeshQuery,
                 filter: *const dtQueryFilter,
             ) -> bool;
+
+            #[rust_name = "move_target_position"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn moveTargetPosition(
+                self: Pin<&mut dtPathCorridor>,
+                new_pos: *const f32,
+                query: *mut dtNavMeshQuery,
+                filter: *const dtQueryFilter,
+            ) -> bool;
+
+            #[rust_name = "optimize_path_visibility"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn optimizePathVisibility(
+                self: Pin<&mut dtPathCorridor>,
+                next: *const f32,
+                path_optimization_range: f32,
+                query: *mut dtNavMeshQuery,
+                filter: *const dtQueryFilter,
+            );
+
+            #[rust_name = "optimize_path_topology"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn optimizePathTopology(
+                self: Pin<&mut dtPathCorridor>,
+                query: *mut dtNavMeshQuery,
+                filter: *const dtQueryFilter,
+            ) -> bool;
+
+            #[rust_name = "move_over_offmesh_connection"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn moveOverOffmeshConnection(
+                self: Pin<&mut dtPathCorridor>,
+                off_mesh_con_ref: dtPolyRef,
+                refs: *mut dtPolyRef,
+                start_pos: *mut f32,
+                end_pos: *mut f32,
+                query: *mut dtNavMeshQuery,
+            ) -> bool;
+
+            #[rust_name = "fix_path_start"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn fixPathStart(
+                self: Pin<&mut dtPathCorridor>,
+                safe_ref: dtPolyRef,
+                safe_pos: *const f32,
+            ) -> bool;
+
+            #[rust_name = "trim_invalid_path"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn trimInvalidPath(
+                self: Pin<&mut dtPathCorridor>,
+                safe_ref: dtPolyRef,
+                safe_pos: *const f32,
+                query: *mut dtNavMeshQuery,
+                filter: *const dtQueryFilter,
+            ) -> bool;
+
+            #[rust_name = "path_corridor_is_valid"]
+            #[cfg(feature = "detour_crowd")]
+            /// Call `dtPathCorridor::isValid`, which modifies neither the corridor nor the query
+            /// despite taking them as non-const.
+            pub unsafe fn pathCorridorIsValid(
+                corridor: &dtPathCorridor,
+                max_look_ahead: i32,
+                query: &dtNavMeshQuery,
+                filter: *const dtQueryFilter,
+            ) -> bool;
+
+            #[rust_name = "get_target"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getTarget(self: &dtPathCorridor) -> *const f32;
+
+            #[rust_name = "get_first_poly"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getFirstPoly(self: &dtPathCorridor) -> dtPolyRef;
+
+            #[rust_name = "get_last_poly"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getLastPoly(self: &dtPathCorridor) -> dtPolyRef;
+
+            #[rust_name = "get_path"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getPath(self: &dtPathCorridor) -> *const dtPolyRef;
+
+            #[rust_name = "init"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn init(
+                self: Pin<&mut dtCrowd>,
+                max_agents: i32,
+                max_agent_radius: f32,
+                navmesh: *mut dtNavMesh,
+            ) -> bool;
+
+            #[rust_name = "get_agent_count"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getAgentCount(self: &dtCrowd) -> i32;
+
+            #[rust_name = "add_agent"]
+            #[cfg(feature = "detour_crowd")]
+            /// Add an agent to the crowd, returning its index or `-1` if the crowd is full.
+            pub fn crowdAddAgent(
+                crowd: Pin<&mut dtCrowd>,
+                position: &[f32; 3],
+                params: &CrowdAgentParams,
+            ) -> i32;
+
+            #[rust_name = "update_agent_parameters"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn crowdUpdateAgentParameters(
+                crowd: Pin<&mut dtCrowd>,
+                index: i32,
+                params: &CrowdAgentParams,
+            );
+
+            #[rust_name = "get_agent_info"]
+            #[cfg(feature = "detour_crowd")]
+            /// Fill `info` with the state of the agent at `index`, returning false if the index is out
+            /// of bounds.
+            pub fn crowdGetAgentInfo(crowd: &dtCrowd, index: i32, info: &mut CrowdAgentInfo) -> bool;
+
+            #[rust_name = "remove_agent"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn removeAgent(self: Pin<&mut dtCrowd>, index: i32);
+
+            #[rust_name = "request_move_target"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn requestMoveTarget(
+                self: Pin<&mut dtCrowd>,
+                index: i32,
+                poly_ref: dtPolyRef,
+                position: *const f32,
+            ) -> bool;
+
+            #[rust_name = "request_move_velocity"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn requestMoveVelocity(
+                self: Pin<&mut dtCrowd>,
+                index: i32,
+                velocity: *const f32,
+            ) -> bool;
+
+            #[rust_name = "reset_move_target"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn resetMoveTarget(self: Pin<&mut dtCrowd>, index: i32) -> bool;
+
+            #[rust_name = "update"]
+            #[cfg(feature = "detour_crowd")]
+            /// Update the crowd simulation, without collecting debug information.
+            pub fn crowdUpdate(crowd: Pin<&mut dtCrowd>, dt: f32);
+
+            #[rust_name = "set_obstacle_avoidance_params"]
+            #[cfg(feature = "detour_crowd")]
+            /// Set the obstacle avoidance configuration `index` of the crowd, returning false if the
+            /// index is out of bounds.
+            pub fn crowdSetObstacleAvoidanceParams(
+                crowd: Pin<&mut dtCrowd>,
+                index: i32,
+                params: &ObstacleAvoidanceConfig,
+            ) -> bool;
+
+            #[rust_name = "get_obstacle_avoidance_params"]
+            #[cfg(feature = "detour_crowd")]
+            /// Fill `params` with the obstacle avoidance configuration `index` of the crowd, returning
+            /// false if the index is out of bounds.
+            pub fn crowdGetObstacleAvoidanceParams(
+                crowd: &dtCrowd,
+                index: i32,
+                params: &mut ObstacleAvoidanceConfig,
+            ) -> bool;
+
+            #[rust_name = "init"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn init(
+                self: Pin<&mut dtPathQueue>,
+                max_path_size: i32,
+                max_search_node_count: i32,
+                navmesh: *mut dtNavMesh,
+            ) -> bool;
+
+            #[rust_name = "update"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn update(self: Pin<&mut dtPathQueue>, max_iters: i32);
+
+            #[rust_name = "request"]
+            #[cfg(feature = "detour_crowd")]
+            /// Queue a path request, returning its reference or `0` if the queue is full.
+            ///
+            /// # Safety
+            ///
+            /// The filter must remain valid until the request has been processed.
+            pub unsafe fn request(
+                self: Pin<&mut dtPathQueue>,
+                start_ref: dtPolyRef,
+                end_ref: dtPolyRef,
+                start_pos: *const f32,
+                end_pos: *const f32,
+                filter: *const dtQueryFilter,
+            ) -> u32;
+
+            #[rust_name = "get_request_status"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getRequestStatus(self: &dtPathQueue, request: u32) -> u32;
+
+            #[rust_name = "get_path_result"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn getPathResult(
+                self: Pin<&mut dtPathQueue>,
+                request: u32,
+                path: *mut dtPolyRef,
+                path_size: *mut i32,
+                max_path: i32,
+            ) -> u32;
+
+            #[rust_name = "init"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn init(self: Pin<&mut dtProximityGrid>, pool_size: i32, cell_size: f32) -> bool;
+
+            #[rust_name = "clear"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn clear(self: Pin<&mut dtProximityGrid>);
+
+            #[rust_name = "add_item"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn addItem(
+                self: Pin<&mut dtProximityGrid>,
+                id: u16,
+                min_x: f32,
+                min_y: f32,
+                max_x: f32,
+                max_y: f32,
+            );
+
+            #[rust_name = "query_items"]
+            #[cfg(feature = "detour_crowd")]
+            pub unsafe fn queryItems(
+                self: &dtProximityGrid,
+                min_x: f32,
+                min_y: f32,
+                max_x: f32,
+                max_y: f32,
+                ids: *mut u16,
+                max_ids: i32,
+            ) -> i32;
+
+            #[rust_name = "get_cell_size"]
+            #[cfg(feature = "detour_crowd")]
+            pub fn getCellSize(self: &dtProximityGrid) -> f32;
+
+            #[rust_name = "set_query_filter"]
+            #[cfg(feature = "detour_crowd")]
+            /// Copy `filter` into the query filter of type `index` of the crowd, returning false if the
+            /// index is out of bounds.
+            pub fn crowdSetQueryFilter(
+                crowd: Pin<&mut dtCrowd>,
+                index: i32,
+                filter: &dtQueryFilter,
+            ) -> bool;
         }
     }
 }
 
+/// Integer type of the Detour polygon and tile references: `u32`, or `u64` if the `polyref64`
+/// feature is enabled.
+#[cfg(all(feature = "detour", not(feature = "polyref64")))]
+pub type RawPolyRef = u32;
+/// Integer type of the Detour polygon and tile references: `u32`, or `u64` if the `polyref64`
+/// feature is enabled.
+#[cfg(all(feature = "detour", feature = "polyref64"))]
+pub type RawPolyRef = u64;
+
+/// Detour polygon reference, `dtPolyRef`.
+#[cfg(feature = "detour")]
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct dtPolyRef(pub RawPolyRef);
+
+#[cfg(feature = "detour")]
+unsafe impl cxx::ExternType for dtPolyRef {
+    type Id = cxx::type_id!("dtPolyRef");
+    type Kind = cxx::kind::Trivial;
+}
+
+/// Detour tile reference, `dtTileRef`.
+#[cfg(feature = "detour")]
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct dtTileRef(pub RawPolyRef);
+
+#[cfg(feature = "detour")]
+unsafe impl cxx::ExternType for dtTileRef {
+    type Id = cxx::type_id!("dtTileRef");
+    type Kind = cxx::kind::Trivial;
+}
+
+/// Source of random numbers in the `[0, 1)` range for the random point queries of
+/// `dtNavMeshQuery`, see [`ffi::detour::find_random_point`].
+///
+/// A panic in the closure aborts the process, as it cannot unwind through Detour.
+#[cfg(feature = "detour")]
+pub struct RandomSource<'a>(pub &'a mut dyn FnMut() -> f32);
+
+#[cfg(feature = "detour")]
+impl RandomSource<'_> {
+    fn next_f32(&mut self) -> f32 {
+        (self.0)()
+    }
+}
+
+/// Callback receiving the batches of polygon references found by
+/// [`ffi::detour::query_polygons_callback`].
+///
+/// A panic in the closure aborts the process, as it cannot unwind through Detour.
+#[cfg(feature = "detour")]
+pub struct PolyQueryCallback<'a>(pub &'a mut dyn FnMut(&[dtPolyRef]));
+
+#[cfg(feature = "detour")]
+impl PolyQueryCallback<'_> {
+    fn process(&mut self, polys: &[dtPolyRef]) {
+        (self.0)(polys)
+    }
+}
+
+/// Polygon passed to the query filter callbacks, along with its reference and its tile.
+#[cfg(feature = "detour")]
+pub type QueryFilterPoly<'a> = (
+    dtPolyRef,
+    &'a ffi::detour::dtMeshTile,
+    &'a ffi::detour::dtPoly,
+);
+
+/// Polygon filtering and cost computation methods of a query filter implemented in Rust, see
+/// [`ffi::detour::new_rust_query_filter`].
+///
+/// These methods correspond to the `passFilter` and `getCost` virtual methods of `dtQueryFilter`.
+/// A panic in one of them aborts the process, as it cannot unwind through Detour.
+#[cfg(feature = "detour")]
+pub trait QueryFilterCallbacks: Send + Sync {
+    fn pass_filter(&self, poly: QueryFilterPoly) -> bool;
+
+    fn get_cost(
+        &self,
+        pa: &[f32; 3],
+        pb: &[f32; 3],
+        prev: Option<QueryFilterPoly>,
+        cur: QueryFilterPoly,
+        next: Option<QueryFilterPoly>,
+    ) -> f32;
+}
+
+/// Boxed [`QueryFilterCallbacks`] implementation, owned by a `RustQueryFilter`.
+#[cfg(feature = "detour")]
+pub struct RustQueryFilterCallbacks(pub Box<dyn QueryFilterCallbacks>);
+
+#[cfg(feature = "detour")]
+impl RustQueryFilterCallbacks {
+    fn pass_filter(
+        &self,
+        poly_ref: dtPolyRef,
+        tile: &ffi::detour::dtMeshTile,
+        poly: &ffi::detour::dtPoly,
+    ) -> bool {
+        self.0.pass_filter((poly_ref, tile, poly))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn get_cost(
+        &self,
+        pa: *const f32,
+        pb: *const f32,
+        prev_ref: dtPolyRef,
+        prev_tile: *const ffi::detour::dtMeshTile,
+        prev_poly: *const ffi::detour::dtPoly,
+        cur_ref: dtPolyRef,
+        cur_tile: *const ffi::detour::dtMeshTile,
+        cur_poly: *const ffi::detour::dtPoly,
+        next_ref: dtPolyRef,
+        next_tile: *const ffi::detour::dtMeshTile,
+        next_poly: *const ffi::detour::dtPoly,
+    ) -> f32 {
+        let optional_poly = |poly_ref: dtPolyRef,
+                             tile: *const ffi::detour::dtMeshTile,
+                             poly: *const ffi::detour::dtPoly| {
+            if poly_ref.0 == 0 {
+                return None;
+            }
+            Some((poly_ref, tile.as_ref()?, poly.as_ref()?))
+        };
+        self.0.get_cost(
+            &*(pa as *const [f32; 3]),
+            &*(pb as *const [f32; 3]),
+            optional_poly(prev_ref, prev_tile, prev_poly),
+            (cur_ref, &*cur_tile, &*cur_poly),
+            optional_poly(next_ref, next_tile, next_poly),
+        )
+    }
+}
+
 unsafe impl Send for ffi::recast::rcPolyMesh {}
 unsafe impl Sync for ffi::recast::rcPolyMesh {}
 unsafe impl Send for ffi::recast::rcPolyMeshDetail {}
@@ -568,10 +1493,29 @@ unsafe impl Sync for ffi::recast::rcPolyMeshDetail {}
 unsafe impl Send for ffi::recast::rcPolyMeshDetailOwned {}
 unsafe impl Sync for ffi::recast::rcPolyMeshDetailOwned {}
 unsafe impl Send for ffi::detour::dtNavMesh {}
+// The const methods of `dtNavMesh` can be called concurrently
+unsafe impl Sync for ffi::detour::dtNavMesh {}
+unsafe impl Send for ffi::detour::dtQueryFilter {}
+unsafe impl Sync for ffi::detour::dtQueryFilter {}
+// The callbacks of `RustQueryFilter` are required to be `Send + Sync`
+unsafe impl Send for ffi::detour::RustQueryFilter {}
+unsafe impl Sync for ffi::detour::RustQueryFilter {}
 unsafe impl Send for ffi::detour::dtNavMeshQuery {}
 unsafe impl Sync for ffi::detour::dtNavMeshQuery {}
 unsafe impl Send for ffi::detour::dtPathCorridor {}
 unsafe impl Sync for ffi::detour::dtPathCorridor {}
+#[cfg(feature = "detour_crowd")]
+unsafe impl Send for ffi::detour::dtCrowd {}
+#[cfg(feature = "detour_crowd")]
+unsafe impl Sync for ffi::detour::dtCrowd {}
+#[cfg(feature = "detour_crowd")]
+unsafe impl Send for ffi::detour::dtPathQueue {}
+#[cfg(feature = "detour_crowd")]
+unsafe impl Sync for ffi::detour::dtPathQueue {}
+#[cfg(feature = "detour_crowd")]
+unsafe impl Send for ffi::detour::dtProximityGrid {}
+#[cfg(feature = "detour_crowd")]
+unsafe impl Sync for ffi::detour::dtProximityGrid {}
 
 impl std::ops::Deref for ffi::recast::rcPolyMeshDetailOwned {
     type Target = ffi::recast::rcPolyMeshDetail;
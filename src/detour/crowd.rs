@@ -0,0 +1,542 @@
+use std::sync::{Arc, RwLock, Weak};
+
+use recast_sys::ffi::detour::*;
+
+use crate::uptr_wrapper;
+
+use super::{Error, NavMesh, OtherError, OwnedNavMesh, PolyRef, QueryFilter};
+
+/// Maximum number of query filter types of a crowd (`DT_CROWD_MAX_QUERY_FILTER_TYPE`).
+pub const CROWD_MAX_QUERY_FILTER_TYPE: u8 = 16;
+/// Maximum number of obstacle avoidance configurations of a crowd
+/// (`DT_CROWD_MAX_OBSTAVOIDANCE_PARAMS`).
+pub const CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS: u8 = 8;
+
+uptr_wrapper!(pub(super) CrowdPriv, dtCrowd, new_crowd);
+
+/// Identifier of an agent of a [`Crowd`].
+///
+/// Identifiers are indices in the agent pool of the crowd, so the identifier of a removed agent
+/// may be reused by an agent added later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AgentId(u32);
+
+impl AgentId {
+    /// Return the index of the agent in the agent pool of its crowd.
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
+/// Steering behaviours of a crowd agent, see [`AgentParams::update_flags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentUpdateFlags {
+    /// Anticipate the turns at the corners of the path.
+    pub anticipate_turns: bool,
+    /// Steer to avoid the other agents and the walls.
+    pub obstacle_avoidance: bool,
+    /// Steer to keep a distance to the other agents, see [`AgentParams::separation_weight`].
+    pub separation: bool,
+    /// Shortcut the path when the next corners are visible.
+    pub optimize_visibility: bool,
+    /// Periodically replan the path to follow changes of the navmesh topology.
+    pub optimize_topology: bool,
+}
+
+impl AgentUpdateFlags {
+    const ANTICIPATE_TURNS: u8 = 1;
+    const OBSTACLE_AVOIDANCE: u8 = 2;
+    const SEPARATION: u8 = 4;
+    const OPTIMIZE_VIS: u8 = 8;
+    const OPTIMIZE_TOPO: u8 = 16;
+
+    fn bits(&self) -> u8 {
+        [
+            (self.anticipate_turns, Self::ANTICIPATE_TURNS),
+            (self.obstacle_avoidance, Self::OBSTACLE_AVOIDANCE),
+            (self.separation, Self::SEPARATION),
+            (self.optimize_visibility, Self::OPTIMIZE_VIS),
+            (self.optimize_topology, Self::OPTIMIZE_TOPO),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |bits, (_, flag)| bits | flag)
+    }
+
+    fn from_bits(bits: u8) -> AgentUpdateFlags {
+        AgentUpdateFlags {
+            anticipate_turns: bits & Self::ANTICIPATE_TURNS != 0,
+            obstacle_avoidance: bits & Self::OBSTACLE_AVOIDANCE != 0,
+            separation: bits & Self::SEPARATION != 0,
+            optimize_visibility: bits & Self::OPTIMIZE_VIS != 0,
+            optimize_topology: bits & Self::OPTIMIZE_TOPO != 0,
+        }
+    }
+}
+
+impl Default for AgentUpdateFlags {
+    /// All behaviours but separation are enabled.
+    fn default() -> Self {
+        AgentUpdateFlags {
+            anticipate_turns: true,
+            obstacle_avoidance: true,
+            separation: false,
+            optimize_visibility: true,
+            optimize_topology: true,
+        }
+    }
+}
+
+/// Configuration of a crowd agent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgentParams {
+    /// Radius of the agent.
+    pub radius: f32,
+    /// Height of the agent.
+    pub height: f32,
+    /// Maximum acceleration of the agent.
+    pub max_acceleration: f32,
+    /// Maximum speed of the agent.
+    pub max_speed: f32,
+    /// Distance within which the other agents and the walls are considered for steering.
+    pub collision_query_range: f32,
+    /// Range of the path visibility optimization.
+    pub path_optimization_range: f32,
+    /// How strongly the agent keeps its distance to the other agents, when separation is enabled.
+    pub separation_weight: f32,
+    /// Steering behaviours of the agent.
+    pub update_flags: AgentUpdateFlags,
+    /// Index of the obstacle avoidance configuration used by the agent, lower than
+    /// [`CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS`].
+    pub obstacle_avoidance_type: u8,
+    /// Index of the query filter used by the agent, lower than [`CROWD_MAX_QUERY_FILTER_TYPE`].
+    /// See [`Crowd::set_query_filter`].
+    pub query_filter_type: u8,
+}
+
+impl Default for AgentParams {
+    /// Parameters of a human sized agent, as in the RecastDemo.
+    fn default() -> Self {
+        AgentParams {
+            radius: 0.6,
+            height: 2.0,
+            max_acceleration: 8.0,
+            max_speed: 3.5,
+            collision_query_range: 0.6 * 12.0,
+            path_optimization_range: 0.6 * 30.0,
+            separation_weight: 2.0,
+            update_flags: AgentUpdateFlags::default(),
+            obstacle_avoidance_type: 0,
+            query_filter_type: 0,
+        }
+    }
+}
+
+impl From<AgentParams> for CrowdAgentParams {
+    fn from(params: AgentParams) -> Self {
+        CrowdAgentParams {
+            radius: params.radius,
+            height: params.height,
+            max_acceleration: params.max_acceleration,
+            max_speed: params.max_speed,
+            collision_query_range: params.collision_query_range,
+            path_optimization_range: params.path_optimization_range,
+            separation_weight: params.separation_weight,
+            update_flags: params.update_flags.bits(),
+            obstacle_avoidance_type: params.obstacle_avoidance_type,
+            query_filter_type: params.query_filter_type,
+        }
+    }
+}
+
+impl From<CrowdAgentParams> for AgentParams {
+    fn from(params: CrowdAgentParams) -> Self {
+        AgentParams {
+            radius: params.radius,
+            height: params.height,
+            max_acceleration: params.max_acceleration,
+            max_speed: params.max_speed,
+            collision_query_range: params.collision_query_range,
+            path_optimization_range: params.path_optimization_range,
+            separation_weight: params.separation_weight,
+            update_flags: AgentUpdateFlags::from_bits(params.update_flags),
+            obstacle_avoidance_type: params.obstacle_avoidance_type,
+            query_filter_type: params.query_filter_type,
+        }
+    }
+}
+
+/// Type of the navmesh polygon traversed by a crowd agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentState {
+    /// The agent is not on the navmesh, e.g. because it was added too far from it.
+    Invalid,
+    /// The agent is walking on a regular polygon.
+    Walking,
+    /// The agent is traversing an off-mesh connection.
+    OffMesh,
+}
+
+impl From<u8> for AgentState {
+    fn from(state: u8) -> Self {
+        match state {
+            1 => AgentState::Walking,
+            2 => AgentState::OffMesh,
+            _ => AgentState::Invalid,
+        }
+    }
+}
+
+/// State of the move request of a crowd agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveRequestState {
+    /// The agent has no move request.
+    None,
+    /// No path to the move target could be found.
+    Failed,
+    /// The agent is following a path to its move target.
+    Valid,
+    /// The path to the move target will be requested on the next update.
+    Requesting,
+    /// The path request is waiting for room in the path queue.
+    WaitingForQueue,
+    /// The path request is being processed.
+    WaitingForPath,
+    /// The agent is moving at the velocity requested with [`Crowd::request_move_velocity`].
+    Velocity,
+}
+
+impl From<u8> for MoveRequestState {
+    fn from(state: u8) -> Self {
+        match state {
+            1 => MoveRequestState::Failed,
+            2 => MoveRequestState::Valid,
+            3 => MoveRequestState::Requesting,
+            4 => MoveRequestState::WaitingForQueue,
+            5 => MoveRequestState::WaitingForPath,
+            6 => MoveRequestState::Velocity,
+            _ => MoveRequestState::None,
+        }
+    }
+}
+
+/// Snapshot of the state of a crowd agent, see [`Crowd::agent`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrowdAgent {
+    /// Type of the polygon traversed by the agent.
+    pub state: AgentState,
+    /// Whether the path followed by the agent does not lead to its move target.
+    pub partial: bool,
+    /// Position of the agent.
+    pub position: [f32; 3],
+    /// Actual velocity of the agent.
+    pub velocity: [f32; 3],
+    /// Velocity the agent would have without steering.
+    pub desired_velocity: [f32; 3],
+    /// Speed the agent would have without steering.
+    pub desired_speed: f32,
+    /// State of the move request of the agent.
+    pub target_state: MoveRequestState,
+    /// Polygon containing the move target, if any.
+    pub target_poly: Option<PolyRef>,
+    /// Requested move target, or velocity when `target_state` is
+    /// [`MoveRequestState::Velocity`].
+    pub target_position: [f32; 3],
+    /// End of the path corridor of the agent, which differs from `target_position` if the path
+    /// is partial.
+    pub corridor_target: [f32; 3],
+    /// Configuration of the agent.
+    pub params: AgentParams,
+}
+
+/// Simulation of a crowd of agents moving on a [`NavMesh`], handling path following, local
+/// steering and collision avoidance.
+///
+/// Like [`PathCorridor`](super::PathCorridor), the crowd internally holds a weak pointer to its
+/// navmesh, and the methods accessing the navmesh fail with an
+/// [`OtherError::DeallocatedResource`] error once it has been deallocated.
+pub struct Crowd {
+    wptr: Weak<RwLock<OwnedNavMesh>>,
+    pub(super) crowd: CrowdPriv,
+}
+
+impl NavMesh {
+    /// Create a new crowd of up to `max_agents` agents, whose radius is at most
+    /// `max_agent_radius`.
+    pub fn new_crowd(&self, max_agents: u32, max_agent_radius: f32) -> crate::Result<Crowd> {
+        let mut crowd = CrowdPriv::new()?;
+        let lock = self.ptr.read().unwrap();
+        // The crowd only uses the navmesh through const navmesh queries
+        let navmesh = lock.as_ref() as *const dtNavMesh as *mut dtNavMesh;
+        let res = unsafe {
+            crowd
+                .pin_mut()
+                .init(max_agents as i32, max_agent_radius, navmesh)
+        };
+        if !res {
+            return Err(Error::Other(OtherError::MemAllocFailed))?;
+        }
+        Ok(Crowd {
+            wptr: Arc::downgrade(&self.ptr),
+            crowd,
+        })
+    }
+}
+
+impl Crowd {
+    /// Add an agent to the crowd at the navmesh point closest to `position`.
+    ///
+    /// Fails with an [`OtherError::CrowdFull`] error if the maximum number of agents is reached,
+    /// and with a [`DetourStatus`](super::DetourStatus) error if the query filter type or the
+    /// obstacle avoidance type of `params` is out of bounds.
+    pub fn add_agent(&mut self, position: [f32; 3], params: AgentParams) -> crate::Result<AgentId> {
+        Self::check_params(&params)?;
+        let navmesh = self.upgrade_navmesh()?;
+        let _lock = navmesh.read().unwrap();
+        let index = add_agent(self.crowd.pin_mut(), &position, &params.into());
+        if index < 0 {
+            return Err(Error::Other(OtherError::CrowdFull))?;
+        }
+        Ok(AgentId(index as u32))
+    }
+
+    /// Remove an agent from the crowd.
+    pub fn remove_agent(&mut self, agent: AgentId) {
+        self.crowd.pin_mut().remove_agent(agent.0 as i32);
+    }
+
+    /// Change the configuration of an agent.
+    ///
+    /// Fails like [`add_agent`](Self::add_agent) if `params` is invalid.
+    pub fn update_agent_params(
+        &mut self,
+        agent: AgentId,
+        params: AgentParams,
+    ) -> crate::Result<()> {
+        self.check_agent(agent)?;
+        Self::check_params(&params)?;
+        update_agent_parameters(self.crowd.pin_mut(), agent.0 as i32, &params.into());
+        Ok(())
+    }
+
+    /// Request an agent to move to `position`, which lies in the `poly` polygon. The path is
+    /// computed during the next updates.
+    pub fn request_move_target(
+        &mut self,
+        agent: AgentId,
+        poly: PolyRef,
+        position: [f32; 3],
+    ) -> crate::Result<()> {
+        self.check_agent(agent)?;
+        let res = unsafe {
+            self.crowd.pin_mut().request_move_target(
+                agent.0 as i32,
+                poly.to_ffi(),
+                position.as_ptr(),
+            )
+        };
+        if !res {
+            return Err(Error::Other(OtherError::InvalidAgent))?;
+        }
+        Ok(())
+    }
+
+    /// Request an agent to move at the specified velocity, instead of towards a target.
+    pub fn request_move_velocity(
+        &mut self,
+        agent: AgentId,
+        velocity: [f32; 3],
+    ) -> crate::Result<()> {
+        self.check_agent(agent)?;
+        let res = unsafe {
+            self.crowd
+                .pin_mut()
+                .request_move_velocity(agent.0 as i32, velocity.as_ptr())
+        };
+        if !res {
+            return Err(Error::Other(OtherError::InvalidAgent))?;
+        }
+        Ok(())
+    }
+
+    /// Cancel the move request of an agent, which stops moving.
+    pub fn reset_move_target(&mut self, agent: AgentId) -> crate::Result<()> {
+        self.check_agent(agent)?;
+        if !self.crowd.pin_mut().reset_move_target(agent.0 as i32) {
+            return Err(Error::Other(OtherError::InvalidAgent))?;
+        }
+        Ok(())
+    }
+
+    /// Advance the simulation by `dt` seconds.
+    pub fn update(&mut self, dt: f32) -> crate::Result<()> {
+        let navmesh = self.upgrade_navmesh()?;
+        let _lock = navmesh.read().unwrap();
+        update(self.crowd.pin_mut(), dt);
+        Ok(())
+    }
+
+    /// Copy `filter` into the query filter of type `index`, used by the agents whose
+    /// [`AgentParams::query_filter_type`] is `index`.
+    ///
+    /// Crowds store plain query filters, so callback based filters are not supported.
+    pub fn set_query_filter(&mut self, index: u8, filter: &QueryFilter) -> crate::Result<()> {
+        if !set_query_filter(self.crowd.pin_mut(), index as i32, filter.as_ref()) {
+            return Err(Error::from(super::DetourStatus::from(
+                super::DetourStatus::DT_FAILURE | super::DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        Ok(())
+    }
+
+    /// Return the maximum number of agents of the crowd.
+    pub fn max_agents(&self) -> u32 {
+        self.crowd.as_ref().get_agent_count() as u32
+    }
+
+    /// Iterate over the identifiers of the agents of the crowd.
+    pub fn agent_ids(&self) -> impl Iterator<Item = AgentId> + '_ {
+        (0..self.max_agents())
+            .map(AgentId)
+            .filter(|&agent| self.agent_info(agent).is_some())
+    }
+
+    /// Return the state of an agent, or `None` if it does not exist.
+    pub fn agent(&self, agent: AgentId) -> Option<CrowdAgent> {
+        let info = self.agent_info(agent)?;
+        Some(CrowdAgent {
+            state: info.state.into(),
+            partial: info.partial,
+            position: info.position,
+            velocity: info.velocity,
+            desired_velocity: info.desired_velocity,
+            desired_speed: info.desired_speed,
+            target_state: info.target_state.into(),
+            target_poly: PolyRef::from_ffi(info.target_ref),
+            target_position: info.target_position,
+            corridor_target: info.corridor_target,
+            params: info.params.into(),
+        })
+    }
+
+    fn agent_info(&self, agent: AgentId) -> Option<CrowdAgentInfo> {
+        let mut info = CrowdAgentInfo {
+            active: false,
+            state: 0,
+            partial: false,
+            position: [0.; 3],
+            velocity: [0.; 3],
+            desired_velocity: [0.; 3],
+            desired_speed: 0.,
+            target_state: 0,
+            target_ref: dtPolyRef::default(),
+            target_position: [0.; 3],
+            corridor_target: [0.; 3],
+            params: AgentParams::default().into(),
+        };
+        let found = get_agent_info(self.crowd.as_ref(), agent.0 as i32, &mut info);
+        (found && info.active).then_some(info)
+    }
+
+    fn check_agent(&self, agent: AgentId) -> crate::Result<()> {
+        if self.agent_info(agent).is_none() {
+            return Err(Error::Other(OtherError::InvalidAgent))?;
+        }
+        Ok(())
+    }
+
+    // Detour uses the types as indexes without checking them
+    fn check_params(params: &AgentParams) -> crate::Result<()> {
+        if params.query_filter_type >= CROWD_MAX_QUERY_FILTER_TYPE
+            || params.obstacle_avoidance_type >= CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS
+        {
+            return Err(Error::from(super::DetourStatus::from(
+                super::DetourStatus::DT_FAILURE | super::DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        Ok(())
+    }
+
+    fn upgrade_navmesh(&self) -> crate::Result<Arc<RwLock<OwnedNavMesh>>> {
+        Ok(self
+            .wptr
+            .upgrade()
+            .ok_or(Error::Other(OtherError::DeallocatedResource))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_crowd() {
+        use super::{
+            AgentParams, AgentState, MoveRequestState, CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS,
+            CROWD_MAX_QUERY_FILTER_TYPE,
+        };
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let (target_poly, target) = query
+            .upgrade()
+            .unwrap()
+            .find_nearest_polygon([5., 0., 5.], [1., 1., 1.], &QueryFilter::default())
+            .unwrap();
+
+        let mut crowd = navmesh.new_crowd(4, 1.).unwrap();
+        let params = AgentParams {
+            radius: 0.5,
+            ..Default::default()
+        };
+        let agent = crowd.add_agent([-5., 0., -5.], params).unwrap();
+        let other = crowd.add_agent([-5., 0., 5.], params).unwrap();
+        let invalid_filter = AgentParams {
+            query_filter_type: CROWD_MAX_QUERY_FILTER_TYPE,
+            ..params
+        };
+        let invalid_avoidance = AgentParams {
+            obstacle_avoidance_type: CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS,
+            ..params
+        };
+        assert!(crowd.add_agent([5., 0., -5.], invalid_filter).is_err());
+        assert!(crowd.add_agent([5., 0., -5.], invalid_avoidance).is_err());
+        assert!(crowd.update_agent_params(other, invalid_filter).is_err());
+        assert!(crowd.update_agent_params(other, invalid_avoidance).is_err());
+        assert_eq!(crowd.agent(other).unwrap().params, params);
+        assert_eq!(crowd.agent_ids().collect::<Vec<_>>(), [agent, other]);
+        crowd.remove_agent(other);
+        assert!(crowd.agent(other).is_none());
+        assert!(crowd.reset_move_target(other).is_err());
+
+        let state = crowd.agent(agent).unwrap();
+        assert_eq!(state.state, AgentState::Walking);
+        assert_eq!(state.target_state, MoveRequestState::None);
+        assert_eq!(state.params, params);
+
+        crowd
+            .request_move_target(agent, target_poly, target)
+            .unwrap();
+        for _ in 0..200 {
+            crowd.update(0.1).unwrap();
+        }
+        let state = crowd.agent(agent).unwrap();
+        assert_eq!(state.target_state, MoveRequestState::Valid);
+        assert_eq!(state.target_poly, Some(target_poly));
+        assert_eq!(state.corridor_target, target);
+        let distance = (0..3)
+            .map(|i| (state.position[i] - target[i]).powi(2))
+            .sum::<f32>()
+            .sqrt();
+        assert!(distance < 0.5);
+
+        crowd.request_move_velocity(agent, [-1., 0., 0.]).unwrap();
+        crowd.update(0.1).unwrap();
+        let state = crowd.agent(agent).unwrap();
+        assert_eq!(state.target_state, MoveRequestState::Velocity);
+        assert!(state.velocity[0] < 0.);
+
+        drop(navmesh);
+        assert!(crowd.update(0.1).is_err());
+    }
+}
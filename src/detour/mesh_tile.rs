@@ -0,0 +1,259 @@
+use std::sync::RwLockReadGuard;
+
+use recast_sys::ffi::detour::*;
+
+use super::{NavMesh, OwnedNavMesh, PolyRef, TileRef};
+
+/// Polygon type of regular navmesh polygons (`DT_POLYTYPE_GROUND`).
+const POLYTYPE_GROUND: u8 = 0;
+/// Index marking the end of a polygon link list (`DT_NULL_LINK`).
+const NULL_LINK: u32 = 0xffffffff;
+/// Side value of the links to polygons of the same tile.
+const INTERNAL_LINK_SIDE: u8 = 0xff;
+
+/// Read access to the tiles of a navmesh, see [`NavMesh::tiles`].
+///
+/// The navmesh is locked for as long as this object exists.
+pub struct NavMeshTiles<'a> {
+    navmesh: RwLockReadGuard<'a, OwnedNavMesh>,
+}
+
+impl NavMesh {
+    /// Lock the navmesh to inspect its tiles, polygons and links.
+    pub fn tiles(&self) -> NavMeshTiles<'_> {
+        NavMeshTiles {
+            navmesh: self.ptr.read().unwrap(),
+        }
+    }
+}
+
+impl NavMeshTiles<'_> {
+    /// Iterate over the tiles of the navmesh, skipping the empty tile slots.
+    pub fn iter(&self) -> impl Iterator<Item = MeshTile<'_>> {
+        let navmesh = self.navmesh.as_ref();
+        (0..navmesh.get_max_tiles())
+            .filter_map(move |i| unsafe { navmesh.get_tile(i).as_ref() })
+            .filter(|tile| mesh_tile_has_header(tile))
+            .map(MeshTile::new)
+    }
+
+    /// Return the reference of a tile of the navmesh.
+    pub fn tile_ref(&self, tile: MeshTile) -> Option<TileRef> {
+        TileRef::from_ffi(unsafe { self.navmesh.as_ref().get_tile_ref(tile.tile as *const _) })
+    }
+
+    /// Return the reference of the polygon at `index` in a tile of the navmesh, or `None` if the
+    /// index is out of bounds.
+    pub fn poly_ref(&self, tile: MeshTile, index: u32) -> Option<PolyRef> {
+        if index as usize >= tile.poly_count() {
+            return None;
+        }
+        let base = unsafe {
+            self.navmesh
+                .as_ref()
+                .get_poly_ref_base(tile.tile as *const _)
+        };
+        PolyRef::new(base.0 | index as recast_sys::RawPolyRef)
+    }
+
+    /// Return the tile and the polygon referred to by `poly_ref`, or `None` if the reference is
+    /// not valid.
+    pub fn tile_and_poly(&self, poly_ref: PolyRef) -> Option<(MeshTile<'_>, Poly<'_>)> {
+        let navmesh = self.navmesh.as_ref();
+        if !navmesh.is_valid_poly_ref(poly_ref.to_ffi()) {
+            return None;
+        }
+        let (mut salt, mut tile, mut poly) = (0, 0, 0);
+        navmesh.decode_poly_id(poly_ref.to_ffi(), &mut salt, &mut tile, &mut poly);
+        // The reference was checked to be valid, so both indices are in bounds
+        let tile = MeshTile::new(unsafe { &*navmesh.get_tile(tile as i32) });
+        let poly = tile.poly(poly as usize)?;
+        Some((tile, poly))
+    }
+}
+
+/// A tile of a navmesh.
+#[derive(Clone, Copy)]
+pub struct MeshTile<'a> {
+    tile: &'a dtMeshTile,
+}
+
+impl<'a> MeshTile<'a> {
+    pub(crate) fn new(tile: &'a dtMeshTile) -> MeshTile<'a> {
+        MeshTile { tile }
+    }
+
+    /// Return the header of the tile, or `None` if the tile slot holds no data.
+    pub fn header(&self) -> Option<MeshTileHeader> {
+        if mesh_tile_has_header(self.tile) {
+            Some(unsafe { mesh_tile_get_header(self.tile) })
+        } else {
+            None
+        }
+    }
+
+    fn poly_count(&self) -> usize {
+        self.header().map_or(0, |header| header.poly_count as usize)
+    }
+
+    /// Return the vertices of the tile, in world space.
+    pub fn vertices(&self) -> &'a [[f32; 3]] {
+        let vert_count = self.header().map_or(0, |header| header.vert_count as usize);
+        crate::slice_from_raw_parts_or_dangling(
+            mesh_tile_get_verts(self.tile) as *const [f32; 3],
+            vert_count,
+        )
+    }
+
+    /// Return the polygon at `index` in the tile, if any.
+    pub fn poly(&self, index: usize) -> Option<Poly<'a>> {
+        if index >= self.poly_count() {
+            return None;
+        }
+        Some(Poly::new(unsafe {
+            mesh_tile_get_poly(self.tile, index as i32)
+        }))
+    }
+
+    /// Iterate over the polygons of the tile. The index of a polygon in this iterator is its
+    /// index in the tile, see [`NavMeshTiles::poly_ref`].
+    pub fn polys(&self) -> impl Iterator<Item = Poly<'a>> {
+        let tile = *self;
+        (0..self.poly_count()).filter_map(move |i| tile.poly(i))
+    }
+
+    /// Iterate over the vertices of a polygon of the tile, in world space.
+    pub fn poly_vertices(&self, poly: Poly<'a>) -> impl Iterator<Item = [f32; 3]> + 'a {
+        let vertices = self.vertices();
+        poly.vertex_indices()
+            .iter()
+            .map(move |&i| vertices[i as usize])
+    }
+
+    /// Iterate over the links of a polygon of the tile to its neighbours.
+    pub fn poly_links(&self, poly: Poly<'a>) -> impl Iterator<Item = Link> + 'a {
+        let tile = self.tile;
+        let mut next = poly_get_first_link(poly.poly);
+        std::iter::from_fn(move || {
+            while next != NULL_LINK {
+                // Link indices come from the polygon or from the previous link
+                let link = unsafe { mesh_tile_get_link(tile, next) };
+                next = link.next;
+                if let Some(neighbour) = PolyRef::from_ffi(link.poly_ref) {
+                    return Some(Link {
+                        neighbour,
+                        edge: link.edge,
+                        side: (link.side != INTERNAL_LINK_SIDE).then_some(link.side),
+                    });
+                }
+            }
+            None
+        })
+    }
+}
+
+/// Link from a navmesh polygon to one of its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Link {
+    /// The neighbour polygon.
+    pub neighbour: PolyRef,
+    /// Index of the polygon edge the link goes through.
+    pub edge: u8,
+    /// Side of the tile the link goes through, for links to polygons of other tiles. Sides are
+    /// numbered from 0 to 7, counter-clockwise starting from +X.
+    pub side: Option<u8>,
+}
+
+/// Type of a navmesh polygon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolyType {
+    /// Regular polygon of the navmesh surface.
+    Ground,
+    /// Polygon representing an off-mesh connection, consisting of its two endpoints.
+    OffMeshConnection,
+}
+
+/// A polygon of a navmesh tile.
+#[derive(Clone, Copy)]
+pub struct Poly<'a> {
+    poly: &'a dtPoly,
+}
+
+impl<'a> Poly<'a> {
+    pub(crate) fn new(poly: &'a dtPoly) -> Poly<'a> {
+        Poly { poly }
+    }
+
+    /// Return the area id of the polygon.
+    pub fn area(&self) -> u8 {
+        self.poly.poly_get_area()
+    }
+
+    /// Return the flags of the polygon.
+    pub fn flags(&self) -> u16 {
+        poly_get_flags(self.poly)
+    }
+
+    /// Return the type of the polygon.
+    pub fn poly_type(&self) -> PolyType {
+        if self.poly.poly_get_type() == POLYTYPE_GROUND {
+            PolyType::Ground
+        } else {
+            PolyType::OffMeshConnection
+        }
+    }
+
+    /// Return the number of vertices of the polygon.
+    pub fn vertex_count(&self) -> u8 {
+        poly_get_vertex_count(self.poly)
+    }
+
+    /// Return the indices of the vertices of the polygon in the vertices of its tile, see
+    /// [`MeshTile::vertices`].
+    pub fn vertex_indices(&self) -> &'a [u16] {
+        crate::slice_from_raw_parts_or_dangling(
+            poly_get_verts(self.poly),
+            self.vertex_count() as usize,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_navmesh_tiles() {
+        use super::PolyType;
+        use crate::detour::test_navmesh;
+
+        let navmesh = test_navmesh();
+
+        let tiles = navmesh.tiles();
+        let all_tiles: Vec<_> = tiles.iter().collect();
+        assert_eq!(all_tiles.len(), 1);
+        let tile = all_tiles[0];
+        let header = tile.header().unwrap();
+        assert_eq!((header.x, header.y, header.layer), (0, 0, 0));
+        assert_eq!(tile.vertices().len(), header.vert_count as usize);
+        assert_eq!(tile.polys().count(), header.poly_count as usize);
+        assert!(tiles.tile_ref(tile).is_some());
+
+        for (i, poly) in tile.polys().enumerate() {
+            assert_eq!(poly.poly_type(), PolyType::Ground);
+            assert_eq!(poly.vertex_indices().len(), poly.vertex_count() as usize);
+            for vertex in tile.poly_vertices(poly) {
+                assert!(vertex[0].abs() <= 10. && vertex[2].abs() <= 10.);
+            }
+            for link in tile.poly_links(poly) {
+                assert!(link.edge < poly.vertex_count());
+                assert!(link.side.is_none());
+                assert!(tiles.tile_and_poly(link.neighbour).is_some());
+            }
+
+            let poly_ref = tiles.poly_ref(tile, i as u32).unwrap();
+            let (_, found) = tiles.tile_and_poly(poly_ref).unwrap();
+            assert_eq!(found.vertex_indices(), poly.vertex_indices());
+        }
+        assert!(tiles.poly_ref(tile, header.poly_count as u32).is_none());
+    }
+}
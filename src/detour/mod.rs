@@ -1,9 +1,55 @@
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::{Arc, RwLock, Weak};
 
 use crate::uptr_wrapper;
 
 use recast_sys::ffi::detour::*;
 
+#[cfg(feature = "detour_crowd")]
+mod crowd;
+mod mesh_tile;
+mod navmesh_set;
+#[cfg(feature = "detour_crowd")]
+mod obstacle_avoidance;
+mod off_mesh_connection;
+#[cfg(feature = "detour_crowd")]
+mod path_corridor;
+#[cfg(feature = "detour_crowd")]
+mod path_queue;
+mod poly_flags;
+mod poly_search;
+#[cfg(feature = "detour_crowd")]
+mod proximity_grid;
+mod query_filter;
+#[cfg(feature = "rand")]
+mod random;
+mod raycast;
+mod refs;
+mod sliced_path;
+mod tile_state;
+mod walls;
+
+#[cfg(feature = "detour_crowd")]
+pub use crowd::*;
+pub use mesh_tile::*;
+#[cfg(feature = "detour_crowd")]
+pub use obstacle_avoidance::*;
+pub use off_mesh_connection::*;
+#[cfg(feature = "detour_crowd")]
+pub use path_corridor::*;
+#[cfg(feature = "detour_crowd")]
+pub use path_queue::*;
+pub use poly_search::*;
+#[cfg(feature = "detour_crowd")]
+pub use proximity_grid::*;
+pub use query_filter::*;
+pub use raycast::*;
+pub use refs::*;
+pub use sliced_path::*;
+pub use walls::*;
+
+/// Number of distinct area ids supported by Detour (`DT_MAX_AREAS`).
+const MAX_AREAS: u8 = 64;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DetourStatus {
     code: u32,
@@ -113,13 +159,20 @@ pub enum OtherError {
     NavMeshCreationFailed,
     #[error("A resource was invalidated")]
     DeallocatedResource,
+    #[error("The crowd has no room for more agents")]
+    CrowdFull,
+    #[error("The crowd agent does not exist")]
+    InvalidAgent,
+    #[error("The path queue is full")]
+    PathQueueFull,
+    #[error("The path request has not been processed yet")]
+    PathRequestPending,
     #[error("An unspecified error occurred")]
     #[default]
     Other,
 }
 
 uptr_wrapper!(pub(crate) OwnedNavMesh, dtNavMesh, new_navmesh);
-uptr_wrapper!(QueryFilter, dtQueryFilter, new_query_filter);
 uptr_wrapper!(pub(crate) NavMeshQueryPriv, dtNavMeshQuery, new_navmesh_query);
 uptr_wrapper!(PathCorridorPriv, dtPathCorridor, new_path_corridor);
 
@@ -148,45 +201,53 @@ impl PathCorridorPriv {
 }
 
 pub struct NavMesh {
-    ptr: Arc<Mutex<OwnedNavMesh>>,
+    ptr: Arc<RwLock<OwnedNavMesh>>,
 }
 
 impl NavMesh {
     /// Create a new single-tile `NavMesh`.
-    pub fn single_tile(mut data: NavMeshCreateParams) -> crate::Result<NavMesh> {
+    ///
+    /// # Safety
+    ///
+    /// Every pointer in `data` must be valid for reads of the number of elements given by the
+    /// matching counts, as required by `dtCreateNavMeshData`. The safe way to build a navmesh
+    /// from Recast data is [`NavMeshBuilder`](crate::recast::NavMeshBuilder).
+    pub unsafe fn single_tile(mut data: NavMeshCreateParams) -> crate::Result<NavMesh> {
         let mut data_ptr: *mut u8 = std::ptr::null_mut();
         let mut data_len: i32 = 0;
-        let res = unsafe {
-            create_navmesh_data(
-                &mut data as *mut NavMeshCreateParams,
-                &mut data_ptr as *mut *mut u8,
-                &mut data_len as *mut i32,
-            )
-        };
+        let res = create_navmesh_data(
+            &mut data as *mut NavMeshCreateParams,
+            &mut data_ptr as *mut *mut u8,
+            &mut data_len as *mut i32,
+        );
         if !res {
             return Err(Error::Other(OtherError::NavMeshCreationFailed))?;
         }
 
         let mut navmesh = OwnedNavMesh::new()?;
-        let res: DetourStatus = unsafe {
-            navmesh
-                .pin_mut()
-                // Let Detour handle freeing the tile data when the navmesh is free'd
-                .init(data_ptr, data_len, dtTileFlags::FreeData.repr)
-                .into()
-        };
+        let res: DetourStatus = navmesh
+            .pin_mut()
+            // Let Detour handle freeing the tile data when the navmesh is free'd
+            .init(data_ptr, data_len, dtTileFlags::FreeData.repr)
+            .into();
         if res.is_failure() {
             return Err(Error::from(res))?;
         }
 
         Ok(NavMesh {
-            ptr: Arc::new(Mutex::new(navmesh)),
+            ptr: Arc::new(RwLock::new(navmesh)),
         })
     }
 
+    /// Return the parameters of the navmesh.
+    pub fn params(&self) -> NavMeshParams {
+        let lock = self.ptr.read().unwrap();
+        navmesh_get_params(lock.as_ref())
+    }
+
     pub fn new_query(&self, max_nodes: u32) -> Result<NavMeshQuery, crate::Error> {
         let mut query = NavMeshQueryPriv::new()?;
-        let lock = self.ptr.lock();
+        let lock = self.ptr.read();
         query.init(lock.as_ref().unwrap(), max_nodes)?;
         Ok(NavMeshQuery {
             wptr: Arc::downgrade(&self.ptr),
@@ -196,8 +257,11 @@ impl NavMesh {
 }
 
 /// Interface to the navmesh query functions. Internally holds an `Arc` making sure the navmesh remains allocated as long as this object is valid.
+///
+/// Each query holds a read lock on the navmesh while it runs, so that it can't be modified by
+/// methods such as [`NavMesh::set_poly_flags`] in the meantime.
 pub struct NavMeshQueryGuard<'q> {
-    ptr: Arc<Mutex<OwnedNavMesh>>,
+    ptr: Arc<RwLock<OwnedNavMesh>>,
     query: &'q mut NavMeshQueryPriv,
 }
 
@@ -211,21 +275,22 @@ impl<'q> NavMeshQueryGuard<'q> {
     /// for you.
     ///
     /// If a path is found, returns a non-empty [`Vec`] of polygon identifiers.
-    pub fn find_path(
+    pub fn find_path<F: AsRef<dtQueryFilter>>(
         &mut self,
         orig: [f32; 3],
-        orig_poly: u32,
+        orig_poly: PolyRef,
         dest: [f32; 3],
-        dest_poly: u32,
+        dest_poly: PolyRef,
         max_len: u32,
-    ) -> crate::Result<Vec<u32>> {
+        filter: &F,
+    ) -> crate::Result<Vec<PolyRef>> {
+        let _lock = self.ptr.read().unwrap();
         let mut path_vec = Vec::with_capacity(max_len as usize);
         let mut path_len = 0;
-        let filter = QueryFilter::default();
         let status: DetourStatus = unsafe {
             let status = self.query.as_ref().find_path(
-                orig_poly,
-                dest_poly,
+                orig_poly.to_ffi(),
+                dest_poly.to_ffi(),
                 orig.as_ptr(),
                 dest.as_ptr(),
                 filter.as_ref() as *const _,
@@ -239,24 +304,24 @@ impl<'q> NavMeshQueryGuard<'q> {
         if status.is_failure() {
             return Err(Error::from(status))?;
         }
-        path_vec.shrink_to_fit();
-        Ok(path_vec)
+        Ok(PolyRef::from_ffi_vec(path_vec))
     }
 
     /// Find the closest navmesh points to the specified origin and destination within the specified search extents,
     /// and compute a path between them, going through a maximum of `max_len` navmesh polygons.
     ///
     /// If a path is found, returns a non-empty [`Vec`] of polygon identifiers.
-    pub fn find_path_search_polys(
+    pub fn find_path_search_polys<F: AsRef<dtQueryFilter>>(
         &mut self,
         orig: [f32; 3],
         dest: [f32; 3],
         half_extents: [f32; 3],
         max_len: u32,
-    ) -> crate::Result<Vec<u32>> {
-        let (orig_poly, orig) = self.find_nearest_polygon(orig, half_extents)?;
-        let (dest_poly, dest) = self.find_nearest_polygon(dest, half_extents)?;
-        self.find_path(orig, orig_poly, dest, dest_poly, max_len)
+        filter: &F,
+    ) -> crate::Result<Vec<PolyRef>> {
+        let (orig_poly, orig) = self.find_nearest_polygon(orig, half_extents, filter)?;
+        let (dest_poly, dest) = self.find_nearest_polygon(dest, half_extents, filter)?;
+        self.find_path(orig, orig_poly, dest, dest_poly, max_len, filter)
     }
 
     /// Return a path straightened by 'string pulling'
@@ -264,10 +329,11 @@ impl<'q> NavMeshQueryGuard<'q> {
         &self,
         start: [f32; 3],
         dest: [f32; 3],
-        path: &[u32],
+        path: &[PolyRef],
         max_len: u32,
         crossings: StraightPathCrossings,
     ) -> crate::Result<StraightPath> {
+        let _lock = self.ptr.read().unwrap();
         let mut straight_path = Vec::with_capacity(max_len as usize * 3);
         let mut path_flags = Vec::with_capacity(max_len as usize);
         let mut path_polys = Vec::with_capacity(max_len as usize);
@@ -277,7 +343,7 @@ impl<'q> NavMeshQueryGuard<'q> {
             let status = self.query.as_ref().find_straight_path(
                 start.as_ptr(),
                 dest.as_ptr(),
-                path.as_ptr(),
+                PolyRef::as_ffi_slice(path).as_ptr(),
                 path.len() as i32,
                 straight_path.as_mut_ptr(),
                 path_flags.as_mut_ptr(),
@@ -301,21 +367,21 @@ impl<'q> NavMeshQueryGuard<'q> {
             .map(|c| c.try_into().unwrap())
             .collect();
         path_flags.shrink_to_fit();
-        path_polys.shrink_to_fit();
         Ok(StraightPath {
             points: straight_path,
             flags: path_flags,
-            polys: path_polys,
+            polys: path_polys.into_iter().map(PolyRef::from_ffi).collect(),
         })
     }
 
-    pub fn get_poly_height(&self, poly: u32, pos: [f32; 3]) -> crate::Result<f32> {
+    pub fn get_poly_height(&self, poly: PolyRef, pos: [f32; 3]) -> crate::Result<f32> {
+        let _lock = self.ptr.read().unwrap();
         let mut height = 0.;
         let status: DetourStatus = unsafe {
             self.query
                 .as_ref()
                 .get_poly_height(
-                    poly,
+                    poly.to_ffi(),
                     pos.as_ptr(),
                     &mut height as *mut f32,
                 )
@@ -327,12 +393,65 @@ impl<'q> NavMeshQueryGuard<'q> {
         Ok(height)
     }
 
+    /// Move from the `start` position lying in the `start_poly` polygon towards the `end`
+    /// position, while staying on the surface of the navmesh, and visiting up to `max_visited`
+    /// polygons.
+    ///
+    /// This is the usual way of moving a character controller on the navmesh without
+    /// pathfinding: the movement slides along the walls it hits. The height of the resulting
+    /// position is snapped to the last visited polygon when possible.
+    pub fn move_along_surface<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        start_poly: PolyRef,
+        start: [f32; 3],
+        end: [f32; 3],
+        filter: &F,
+        max_visited: u32,
+    ) -> crate::Result<SurfaceMove> {
+        let mut position = [0.; 3];
+        let mut visited = Vec::with_capacity(max_visited as usize);
+        let mut visited_len = 0;
+        let status: DetourStatus = unsafe {
+            // Released before the height query, which takes its own lock
+            let _lock = self.ptr.read().unwrap();
+            let status = self.query.as_ref().move_along_surface(
+                start_poly.to_ffi(),
+                start.as_ptr(),
+                end.as_ptr(),
+                filter.as_ref() as *const _,
+                position.as_mut_ptr(),
+                visited.as_mut_ptr(),
+                &mut visited_len as *mut i32,
+                max_visited as i32,
+            );
+            visited.set_len(visited_len as usize);
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+
+        // The height query fails when the position lies on the edge of the polygon, in which case
+        // the height computed by moveAlongSurface is good enough
+        let visited = PolyRef::from_ffi_vec(visited);
+        if let Some(&last_poly) = visited.last() {
+            if let Ok(height) = self.get_poly_height(last_poly, position) {
+                position[1] = height;
+            }
+        }
+        Ok(SurfaceMove { position, visited })
+    }
+
     /// Construct a new path corridor from a path returned by one of the `find_path` methods.
-    pub fn new_corridor(
-        &self,
+    ///
+    /// Fails with a [`DetourStatus`] error for which [`DetourStatus::is_invalid_param`] is true if
+    /// a polygon of the path is not valid, or does not pass the filter.
+    pub fn new_corridor<F: AsRef<dtQueryFilter>>(
+        &mut self,
         start: [f32; 3],
         end: [f32; 3],
-        path: &[u32],
+        path: &[PolyRef],
+        filter: &F,
     ) -> crate::Result<PathCorridor> {
         if path.is_empty() {
             // TODO: might be better to throw an error here ?
@@ -345,7 +464,7 @@ impl<'q> NavMeshQueryGuard<'q> {
         unsafe {
             dt_path
                 .pin_mut()
-                .reset(*path.first().unwrap(), start.as_ptr());
+                .reset(path.first().unwrap().to_ffi(), start.as_ptr());
         }
 
         let mut corridor = PathCorridor {
@@ -357,24 +476,44 @@ impl<'q> NavMeshQueryGuard<'q> {
             corridor
                 .path
                 .pin_mut()
-                .set_corridor(end.as_ptr(), path.as_ptr(), path_len as i32);
+                .set_corridor(
+                    end.as_ptr(),
+                    PolyRef::as_ffi_slice(path).as_ptr(),
+                    path_len as i32,
+                );
+        };
+
+        let valid = unsafe {
+            let _lock = self.ptr.read().unwrap();
+            path_corridor_is_valid(
+                corridor.path.as_ref(),
+                path_len as i32,
+                self.query.as_ref(),
+                filter.as_ref() as *const _,
+            )
         };
+        if !valid {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
 
         Ok(corridor)
     }
 
     pub fn closest_point_on_poly(
         &self,
-        poly_ref: u32,
+        poly_ref: PolyRef,
         pos: [f32; 3],
     ) -> crate::Result<([f32; 3], bool)> {
+        let _lock = self.ptr.read().unwrap();
         let mut pos_over_poly = false;
         let mut closest = [0.; 3];
         let status: DetourStatus = unsafe {
             self.query
                 .as_ref()
                 .closest_point_on_poly(
-                    poly_ref,
+                    poly_ref.to_ffi(),
                     pos.as_ptr(),
                     closest.as_mut_ptr(),
                     &mut pos_over_poly as *mut bool,
@@ -387,15 +526,17 @@ impl<'q> NavMeshQueryGuard<'q> {
         Ok((closest, pos_over_poly))
     }
 
-    /// Search for the nearest polygon in a box around `p`. Returns the detour polygon id of the found polygon if any, 0 if none.
-    pub fn find_nearest_polygon(
+    /// Search for the nearest polygon in a box around `p`. Returns the reference of the found polygon and the nearest
+    /// point on it, or an [`OtherError::NoPolyFound`] error if there is none.
+    pub fn find_nearest_polygon<F: AsRef<dtQueryFilter>>(
         &self,
         p: [f32; 3],
         half_extents: [f32; 3],
-    ) -> crate::Result<(u32, [f32; 3])> {
-        let mut nearest_ref = 0;
+        filter: &F,
+    ) -> crate::Result<(PolyRef, [f32; 3])> {
+        let _lock = self.ptr.read().unwrap();
+        let mut nearest_ref = dtPolyRef::default();
         let mut nearest = [0.; 3];
-        let filter = QueryFilter::default();
         let status: DetourStatus = unsafe {
             self.query
                 .as_ref()
@@ -412,7 +553,7 @@ impl<'q> NavMeshQueryGuard<'q> {
             return Err(Error::from(status))?;
         }
 
-        if nearest_ref != 0 {
+        if let Some(nearest_ref) = PolyRef::from_ffi(nearest_ref) {
             Ok((nearest_ref, nearest))
         } else {
             Err(Error::from(OtherError::NoPolyFound))?
@@ -425,7 +566,7 @@ impl<'q> NavMeshQueryGuard<'q> {
 /// constructed by upgrading the internal weak pointer.
 #[derive(Default)]
 pub struct NavMeshQuery {
-    wptr: Weak<Mutex<OwnedNavMesh>>,
+    wptr: Weak<RwLock<OwnedNavMesh>>,
     pub(crate) dt_query: NavMeshQueryPriv,
 }
 
@@ -474,22 +615,32 @@ impl From<StraightPathCrossings> for dtStraightPathOptions {
     }
 }
 
+/// Result of the [`NavMeshQueryGuard::move_along_surface`] query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceMove {
+    /// The position reached by the move.
+    pub position: [f32; 3],
+    /// The polygons visited by the move. The last one contains the resulting position.
+    pub visited: Vec<PolyRef>,
+}
+
 #[derive(Debug, Clone)]
 pub struct StraightPath {
     pub points: Vec<[f32; 3]>,
     pub flags: Vec<u8>,
-    pub polys: Vec<u32>,
+    /// The polygons the points lie in. The end point does not have one.
+    pub polys: Vec<Option<PolyRef>>,
 }
 
 #[derive(Debug)]
 pub struct Corners {
     vertices: Vec<f32>,
     flags: Vec<u8>,
-    polys: Vec<u32>,
+    polys: Vec<Option<PolyRef>>,
 }
 
 impl Corners {
-    pub fn into_parts(self) -> (Vec<f32>, Vec<u8>, Vec<u32>) {
+    pub fn into_parts(self) -> (Vec<f32>, Vec<u8>, Vec<Option<PolyRef>>) {
         (self.vertices, self.flags, self.polys)
     }
 
@@ -501,11 +652,11 @@ impl Corners {
         self.flags.iter().cloned()
     }
 
-    pub fn polys(&self) -> impl ExactSizeIterator<Item = u32> + '_ {
+    pub fn polys(&self) -> impl ExactSizeIterator<Item = Option<PolyRef>> + '_ {
         self.polys.iter().cloned()
     }
 
-    pub fn iter(&self) -> impl ExactSizeIterator<Item = ([f32; 3], u8, u32)> + '_ {
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = ([f32; 3], u8, Option<PolyRef>)> + '_ {
         self.vertices()
             .zip(self.flags())
             .zip(self.polys())
@@ -516,26 +667,30 @@ impl Corners {
 /// Interface to the path corridor functions. Upgraded version of the weak pointer based
 /// [`PathCorridor`] which guarantees the navmesh is valid as long as the object exists.
 pub struct PathCorridorGuard<'q> {
-    _ptr: Arc<Mutex<OwnedNavMesh>>,
+    ptr: Arc<RwLock<OwnedNavMesh>>,
     path: &'q mut PathCorridorPriv,
 }
 
 impl<'q> PathCorridorGuard<'q> {
-    pub fn reset(&mut self, poly: u32, position: [f32; 3]) {
+    pub fn reset(&mut self, poly: PolyRef, position: [f32; 3]) {
         unsafe {
-            self.path.pin_mut().reset(poly, position.as_ptr());
+            self.path.pin_mut().reset(poly.to_ffi(), position.as_ptr());
         }
     }
 
-    pub fn find_corners(
+    /// Return up to `max_corners` corners of the path ahead of the corridor position.
+    ///
+    /// The filter is currently unused by Detour, which string-pulls the corridor as it is.
+    pub fn find_corners<F: AsRef<dtQueryFilter>>(
         &mut self,
         max_corners: i32,
         query: &mut NavMeshQueryGuard,
+        filter: &F,
     ) -> Option<Corners> {
+        let _lock = query.ptr.read().unwrap();
         let mut vertices = Vec::with_capacity(3 * max_corners as usize);
         let mut flags = Vec::with_capacity(max_corners as usize);
         let mut polys = Vec::with_capacity(max_corners as usize);
-        let filter = QueryFilter::new().unwrap();
         unsafe {
             let corners_len = self.path.pin_mut().find_corners(
                 vertices.as_mut_ptr(),
@@ -552,11 +707,10 @@ impl<'q> PathCorridorGuard<'q> {
                 polys.set_len(corners_len as usize);
                 vertices.shrink_to_fit();
                 flags.shrink_to_fit();
-                polys.shrink_to_fit();
                 return Some(Corners {
                     vertices,
                     flags,
-                    polys,
+                    polys: polys.into_iter().map(PolyRef::from_ffi).collect(),
                 });
             }
         };
@@ -565,12 +719,13 @@ impl<'q> PathCorridorGuard<'q> {
 
     /// Attempt to move the corridor to the specified position. If the move was successful, returns
     /// the new position, constrained to the surface of the navmesh.
-    pub fn move_position(
+    pub fn move_position<F: AsRef<dtQueryFilter>>(
         &mut self,
         new_position: [f32; 3],
         query: &mut NavMeshQueryGuard,
+        filter: &F,
     ) -> Option<[f32; 3]> {
-        let filter = QueryFilter::new().unwrap();
+        let _lock = query.ptr.read().unwrap();
         let res = unsafe {
             self.path.pin_mut().move_position(
                 new_position.as_ptr(),
@@ -601,7 +756,7 @@ impl<'q> PathCorridorGuard<'q> {
 /// corridors will be invalidated along with their navmesh.
 #[derive(Default)]
 pub struct PathCorridor {
-    wptr: Weak<Mutex<OwnedNavMesh>>,
+    wptr: Weak<RwLock<OwnedNavMesh>>,
     path: PathCorridorPriv,
 }
 
@@ -628,7 +783,7 @@ impl PathCorridor {
     pub fn upgrade(&mut self) -> Option<PathCorridorGuard> {
         if let Some(arc) = self.wptr.upgrade() {
             Some(PathCorridorGuard {
-                _ptr: arc,
+                ptr: arc,
                 path: &mut self.path,
             })
         } else {
@@ -636,3 +791,61 @@ impl PathCorridor {
         }
     }
 }
+
+/// Build the navmesh shared by the tests, a single tile covering the quad of
+/// [`test_quad`](crate::recast::test_quad).
+#[cfg(all(test, feature = "recast"))]
+pub(crate) fn test_navmesh() -> NavMesh {
+    test_navmesh_with_connections(&[])
+}
+
+/// Build the navmesh of [`test_navmesh`], with the specified off-mesh connections.
+#[cfg(all(test, feature = "recast"))]
+pub(crate) fn test_navmesh_with_connections(connections: &[OffMeshConnection]) -> NavMesh {
+    let (mut context, mesh) = crate::recast::test_quad();
+    let (_, navmesh) = context
+        .default_pipeline_detour_with_connections(&[mesh], connections)
+        .unwrap();
+    navmesh
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_query_guard_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<super::NavMeshQueryGuard>();
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_move_along_surface() {
+        use super::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let (start_poly, start) = query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .unwrap();
+
+        // Moving inside the navmesh reaches the target
+        let target = [2., 0., -3.];
+        let result = query
+            .move_along_surface(start_poly, start, target, &filter, 16)
+            .unwrap();
+        assert!(!result.visited.is_empty());
+        assert!((result.position[0] - 2.).abs() < 1e-3);
+        assert!((result.position[2] + 3.).abs() < 1e-3);
+        assert!(result.position[1].abs() < 1.);
+
+        // Moving into a wall slides along it, staying inside the navmesh
+        let result = query
+            .move_along_surface(start_poly, start, [5., 0., 20.], &filter, 16)
+            .unwrap();
+        assert!(result.position[2] < 10.);
+        assert!(result.position[0] > 4.);
+    }
+}
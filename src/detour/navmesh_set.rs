@@ -0,0 +1,251 @@
+use std::{
+    io::{Read, Write},
+    sync::{Arc, RwLock},
+};
+
+use recast_sys::ffi::detour::*;
+
+use super::{DetourStatus, Error, NavMesh, OwnedNavMesh, RawPolyRef};
+
+/// Magic number of the RecastDemo navmesh set format, `'MSET'`.
+const NAVMESHSET_MAGIC: i32 =
+    (b'M' as i32) << 24 | (b'S' as i32) << 16 | (b'E' as i32) << 8 | b'T' as i32;
+const NAVMESHSET_VERSION: i32 = 1;
+/// Size of the padding at the end of the tile headers, which contain a tile ref and an `i32` data
+/// size. It is only non-empty with 64 bit tile refs.
+const TILE_HEADER_PADDING: usize = std::mem::size_of::<RawPolyRef>() - std::mem::size_of::<i32>();
+
+impl NavMesh {
+    /// Write the navmesh parameters and the data of all of its tiles to `writer`, using the
+    /// navmesh set format of the RecastDemo "Save" feature.
+    ///
+    /// Like in the RecastDemo, the data is written in the native byte order, so the output is only
+    /// portable to platforms of the same endianness.
+    pub fn save<W: Write>(&self, mut writer: W) -> crate::Result<()> {
+        let lock = self.ptr.read().unwrap();
+        let navmesh = lock.as_ref();
+
+        let tiles = (0..navmesh.get_max_tiles())
+            .filter_map(|i| unsafe { navmesh.get_tile(i).as_ref() })
+            .filter(|tile| mesh_tile_has_header(tile) && mesh_tile_get_data_size(tile) > 0)
+            .collect::<Vec<_>>();
+
+        let params = navmesh_get_params(navmesh);
+        writer.write_all(&NAVMESHSET_MAGIC.to_ne_bytes())?;
+        writer.write_all(&NAVMESHSET_VERSION.to_ne_bytes())?;
+        writer.write_all(&(tiles.len() as i32).to_ne_bytes())?;
+        for v in params.origin {
+            writer.write_all(&v.to_ne_bytes())?;
+        }
+        writer.write_all(&params.tile_width.to_ne_bytes())?;
+        writer.write_all(&params.tile_height.to_ne_bytes())?;
+        writer.write_all(&params.max_tiles.to_ne_bytes())?;
+        writer.write_all(&params.max_polys.to_ne_bytes())?;
+
+        for tile in tiles {
+            let tile_ref = unsafe { navmesh.get_tile_ref(tile as *const _) };
+            let data_size = mesh_tile_get_data_size(tile);
+            let data = crate::slice_from_raw_parts_or_dangling(
+                mesh_tile_get_data(tile),
+                data_size as usize,
+            );
+            writer.write_all(&tile_ref.0.to_ne_bytes())?;
+            writer.write_all(&data_size.to_ne_bytes())?;
+            writer.write_all(&[0; TILE_HEADER_PADDING])?;
+            writer.write_all(data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a navmesh written by [`save`](Self::save), or by the RecastDemo "Save" feature.
+    ///
+    /// Fails with a [`DetourStatus`] error for which [`DetourStatus::is_wrong_magic`] or
+    /// [`DetourStatus::is_wrong_version`] is true if the data is not in a supported format, and
+    /// for which [`DetourStatus::is_invalid_param`] is true if the size of a tile does not match
+    /// its header.
+    pub fn load<R: Read>(mut reader: R) -> crate::Result<NavMesh> {
+        if read_i32(&mut reader)? != NAVMESHSET_MAGIC {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_WRONG_MAGIC,
+            )))?;
+        }
+        if read_i32(&mut reader)? != NAVMESHSET_VERSION {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_WRONG_VERSION,
+            )))?;
+        }
+        let num_tiles = read_i32(&mut reader)?;
+        let params = NavMeshParams {
+            origin: [
+                read_f32(&mut reader)?,
+                read_f32(&mut reader)?,
+                read_f32(&mut reader)?,
+            ],
+            tile_width: read_f32(&mut reader)?,
+            tile_height: read_f32(&mut reader)?,
+            max_tiles: read_i32(&mut reader)?,
+            max_polys: read_i32(&mut reader)?,
+        };
+
+        let mut navmesh = OwnedNavMesh::new()?;
+        let status: DetourStatus = init_with_params(navmesh.pin_mut(), &params).into();
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+
+        for _ in 0..num_tiles {
+            let tile_ref = read_tile_ref(&mut reader)?;
+            let data_size = read_i32(&mut reader)?;
+            reader.read_exact(&mut [0; TILE_HEADER_PADDING])?;
+            if tile_ref == 0 || data_size <= 0 {
+                break;
+            }
+
+            let mut data = vec![0; data_size as usize];
+            reader.read_exact(&mut data)?;
+            // Detour trusts the layout of the tile data, reject anything it would read out of
+            let status: DetourStatus = check_navmesh_data(&data).into();
+            if status.is_failure() {
+                return Err(Error::from(status))?;
+            }
+
+            let data_ptr = alloc_navmesh_data(data_size);
+            if data_ptr.is_null() {
+                return Err(Error::Other(super::OtherError::MemAllocFailed))?;
+            }
+            unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), data_ptr, data.len()) };
+
+            let status: DetourStatus = unsafe {
+                navmesh
+                    .pin_mut()
+                    // Let Detour handle freeing the tile data when the navmesh is free'd
+                    .add_tile(
+                        data_ptr,
+                        data_size,
+                        dtTileFlags::FreeData.repr,
+                        recast_sys::dtTileRef(tile_ref),
+                        std::ptr::null_mut(),
+                    )
+                    .into()
+            };
+            if status.is_failure() {
+                unsafe { free_navmesh_data(data_ptr) };
+                return Err(Error::from(status))?;
+            }
+        }
+
+        Ok(NavMesh {
+            ptr: Arc::new(RwLock::new(navmesh)),
+        })
+    }
+}
+
+fn read_i32<R: Read>(reader: &mut R) -> std::io::Result<i32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_ne_bytes(buf))
+}
+
+fn read_tile_ref<R: Read>(reader: &mut R) -> std::io::Result<RawPolyRef> {
+    let mut buf = [0; std::mem::size_of::<RawPolyRef>()];
+    reader.read_exact(&mut buf)?;
+    Ok(RawPolyRef::from_ne_bytes(buf))
+}
+
+fn read_f32<R: Read>(reader: &mut R) -> std::io::Result<f32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(f32::from_ne_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::detour::{Error, NavMesh, QueryFilter};
+
+    #[test]
+    fn test_load_wrong_magic() {
+        let data = [0u8; 64];
+        match NavMesh::load(&data[..]) {
+            Err(crate::Error::Detour(Error::Detour(Some(status)))) => {
+                assert!(status.is_failure());
+                assert!(status.is_wrong_magic());
+            }
+            _ => panic!("Expected a wrong magic error"),
+        }
+    }
+
+    #[test]
+    fn test_load_wrong_version() {
+        let mut data = super::NAVMESHSET_MAGIC.to_ne_bytes().to_vec();
+        data.extend((super::NAVMESHSET_VERSION + 1).to_ne_bytes());
+        data.resize(64, 0);
+        match NavMesh::load(data.as_slice()) {
+            Err(crate::Error::Detour(Error::Detour(Some(status)))) => {
+                assert!(status.is_wrong_version());
+            }
+            _ => panic!("Expected a wrong version error"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_save_load_roundtrip() {
+        use crate::detour::test_navmesh;
+
+        let navmesh = test_navmesh();
+
+        let mut data = Vec::new();
+        navmesh.save(&mut data).unwrap();
+        let loaded = NavMesh::load(data.as_slice()).unwrap();
+        assert_eq!(loaded.params(), navmesh.params());
+
+        let mut resaved = Vec::new();
+        loaded.save(&mut resaved).unwrap();
+        assert_eq!(data, resaved);
+
+        let mut query = loaded.new_query(256).unwrap();
+        let query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+        assert!(query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_load_invalid_tile() {
+        use crate::detour::{test_navmesh, RawPolyRef};
+
+        let mut data = Vec::new();
+        test_navmesh().save(&mut data).unwrap();
+        // Set header, then the tile ref and data size of the first tile
+        let data_size_offset = 40 + std::mem::size_of::<RawPolyRef>();
+        let tile_offset = 40 + 2 * std::mem::size_of::<RawPolyRef>();
+
+        let mut truncated = data.clone();
+        let data_size = i32::from_ne_bytes(
+            data[data_size_offset..data_size_offset + 4]
+                .try_into()
+                .unwrap(),
+        );
+        truncated[data_size_offset..data_size_offset + 4]
+            .copy_from_slice(&(data_size - 4).to_ne_bytes());
+        truncated.truncate(data.len() - 4);
+        match NavMesh::load(truncated.as_slice()) {
+            Err(crate::Error::Detour(Error::Detour(Some(status)))) => {
+                assert!(status.is_invalid_param());
+            }
+            _ => panic!("Expected an invalid param error"),
+        }
+
+        let mut wrong_magic = data;
+        wrong_magic[tile_offset] ^= 0xff;
+        match NavMesh::load(wrong_magic.as_slice()) {
+            Err(crate::Error::Detour(Error::Detour(Some(status)))) => {
+                assert!(status.is_wrong_magic());
+            }
+            _ => panic!("Expected a wrong magic error"),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use recast_sys::ffi::detour::*;
+
+use super::{Crowd, DetourStatus, Error};
+
+/// Configuration of the obstacle avoidance of crowd agents, see
+/// [`Crowd::set_obstacle_avoidance_params`].
+///
+/// The crowd samples candidate velocities around the desired velocity of an agent, and picks the
+/// one with the lowest penalty. More samples give smoother avoidance at a higher CPU cost, see
+/// the [`low`](Self::low), [`medium`](Self::medium), [`high`](Self::high) and
+/// [`ultra`](Self::ultra) presets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObstacleAvoidanceParams {
+    /// Bias of the sampling towards the desired velocity, between `0.0` and `1.0`.
+    pub vel_bias: f32,
+    /// Weight of the penalty for deviating from the desired velocity.
+    pub weight_des_vel: f32,
+    /// Weight of the penalty for deviating from the current velocity.
+    pub weight_cur_vel: f32,
+    /// Weight of the penalty for passing other agents on the wrong side.
+    pub weight_side: f32,
+    /// Weight of the penalty for the time to impact.
+    pub weight_toi: f32,
+    /// Time horizon of the collision prediction, in seconds.
+    pub horiz_time: f32,
+    /// Size of the grid of the grid sampling mode, which the crowd does not use.
+    pub grid_size: u8,
+    /// Number of sectors of the adaptive sampling pattern.
+    pub adaptive_divs: u8,
+    /// Number of rings of the adaptive sampling pattern.
+    pub adaptive_rings: u8,
+    /// Number of refinement iterations of the adaptive sampling.
+    pub adaptive_depth: u8,
+}
+
+impl ObstacleAvoidanceParams {
+    /// Low quality preset of the RecastDemo, sampling 11 velocities.
+    pub fn low() -> Self {
+        Self::preset(5, 2, 1)
+    }
+
+    /// Medium quality preset of the RecastDemo, sampling 22 velocities.
+    pub fn medium() -> Self {
+        Self::preset(5, 2, 2)
+    }
+
+    /// High quality preset of the RecastDemo, sampling 45 velocities.
+    pub fn high() -> Self {
+        Self::preset(7, 2, 3)
+    }
+
+    /// Ultra quality preset of the RecastDemo, sampling 66 velocities.
+    pub fn ultra() -> Self {
+        Self::preset(7, 3, 3)
+    }
+
+    fn preset(adaptive_divs: u8, adaptive_rings: u8, adaptive_depth: u8) -> Self {
+        ObstacleAvoidanceParams {
+            vel_bias: 0.5,
+            adaptive_divs,
+            adaptive_rings,
+            adaptive_depth,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ObstacleAvoidanceParams {
+    /// The configuration initially used by all the profiles of a crowd.
+    fn default() -> Self {
+        ObstacleAvoidanceParams {
+            vel_bias: 0.4,
+            weight_des_vel: 2.0,
+            weight_cur_vel: 0.75,
+            weight_side: 0.75,
+            weight_toi: 2.5,
+            horiz_time: 2.5,
+            grid_size: 33,
+            adaptive_divs: 7,
+            adaptive_rings: 2,
+            adaptive_depth: 5,
+        }
+    }
+}
+
+impl From<ObstacleAvoidanceParams> for ObstacleAvoidanceConfig {
+    fn from(params: ObstacleAvoidanceParams) -> Self {
+        ObstacleAvoidanceConfig {
+            vel_bias: params.vel_bias,
+            weight_des_vel: params.weight_des_vel,
+            weight_cur_vel: params.weight_cur_vel,
+            weight_side: params.weight_side,
+            weight_toi: params.weight_toi,
+            horiz_time: params.horiz_time,
+            grid_size: params.grid_size,
+            adaptive_divs: params.adaptive_divs,
+            adaptive_rings: params.adaptive_rings,
+            adaptive_depth: params.adaptive_depth,
+        }
+    }
+}
+
+impl From<ObstacleAvoidanceConfig> for ObstacleAvoidanceParams {
+    fn from(config: ObstacleAvoidanceConfig) -> Self {
+        ObstacleAvoidanceParams {
+            vel_bias: config.vel_bias,
+            weight_des_vel: config.weight_des_vel,
+            weight_cur_vel: config.weight_cur_vel,
+            weight_side: config.weight_side,
+            weight_toi: config.weight_toi,
+            horiz_time: config.horiz_time,
+            grid_size: config.grid_size,
+            adaptive_divs: config.adaptive_divs,
+            adaptive_rings: config.adaptive_rings,
+            adaptive_depth: config.adaptive_depth,
+        }
+    }
+}
+
+impl Crowd {
+    /// Set the obstacle avoidance profile `index`, used by the agents whose
+    /// [`AgentParams::obstacle_avoidance_type`](super::AgentParams::obstacle_avoidance_type) is
+    /// `index`.
+    ///
+    /// Fails if `index` is not lower than
+    /// [`CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS`](super::CROWD_MAX_OBSTACLE_AVOIDANCE_PARAMS).
+    pub fn set_obstacle_avoidance_params(
+        &mut self,
+        index: u8,
+        params: &ObstacleAvoidanceParams,
+    ) -> crate::Result<()> {
+        if !set_obstacle_avoidance_params(self.crowd.pin_mut(), index as i32, &(*params).into()) {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        Ok(())
+    }
+
+    /// Return the obstacle avoidance profile `index`, or `None` if the index is out of bounds.
+    pub fn obstacle_avoidance_params(&self, index: u8) -> Option<ObstacleAvoidanceParams> {
+        let mut config = ObstacleAvoidanceParams::default().into();
+        get_obstacle_avoidance_params(self.crowd.as_ref(), index as i32, &mut config)
+            .then(|| config.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObstacleAvoidanceParams;
+
+    #[test]
+    fn test_presets() {
+        // Table of the RecastDemo crowd tool: divs, rings, depth, and number of sampled velocities
+        let presets = [
+            (ObstacleAvoidanceParams::low(), 5, 2, 1, 11),
+            (ObstacleAvoidanceParams::medium(), 5, 2, 2, 22),
+            (ObstacleAvoidanceParams::high(), 7, 2, 3, 45),
+            (ObstacleAvoidanceParams::ultra(), 7, 3, 3, 66),
+        ];
+        for (params, divs, rings, depth, samples) in presets {
+            assert_eq!(params.vel_bias, 0.5);
+            assert_eq!(params.adaptive_divs, divs);
+            assert_eq!(params.adaptive_rings, rings);
+            assert_eq!(params.adaptive_depth, depth);
+            // Each refinement iteration samples the desired velocity and each point of the pattern
+            assert_eq!(depth as u32 * (1 + divs as u32 * rings as u32), samples);
+
+            // The other fields keep their default values
+            let defaults = ObstacleAvoidanceParams::default();
+            assert_eq!(
+                ObstacleAvoidanceParams {
+                    vel_bias: defaults.vel_bias,
+                    adaptive_divs: defaults.adaptive_divs,
+                    adaptive_rings: defaults.adaptive_rings,
+                    adaptive_depth: defaults.adaptive_depth,
+                    ..params
+                },
+                defaults
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_crowd_obstacle_avoidance_params() {
+        use crate::detour::test_navmesh;
+
+        let navmesh = test_navmesh();
+        let mut crowd = navmesh.new_crowd(4, 1.).unwrap();
+
+        assert_eq!(
+            crowd.obstacle_avoidance_params(0),
+            Some(ObstacleAvoidanceParams::default())
+        );
+        crowd
+            .set_obstacle_avoidance_params(3, &ObstacleAvoidanceParams::ultra())
+            .unwrap();
+        assert_eq!(
+            crowd.obstacle_avoidance_params(3),
+            Some(ObstacleAvoidanceParams::ultra())
+        );
+        assert!(crowd
+            .set_obstacle_avoidance_params(8, &ObstacleAvoidanceParams::low())
+            .is_err());
+        assert!(crowd.obstacle_avoidance_params(8).is_none());
+    }
+}
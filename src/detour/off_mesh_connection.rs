@@ -0,0 +1,139 @@
+use recast_sys::ffi::detour::NavMeshCreateParams;
+
+use super::{DetourStatus, Error, MAX_AREAS};
+
+/// Direction flag of bidirectional off-mesh connections (`DT_OFFMESH_CON_BIDIR`).
+const OFF_MESH_CON_BIDIR: u8 = 1;
+
+/// A link between two points of the navmesh which are not connected by its surface, such as the
+/// two ends of a ladder, a jump-down or a teleporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffMeshConnection {
+    /// Start point of the connection.
+    pub start: [f32; 3],
+    /// End point of the connection.
+    pub end: [f32; 3],
+    /// Radius around the endpoints within which the connection connects to the navmesh.
+    pub radius: f32,
+    /// Whether the connection can be traversed from its end to its start too.
+    pub bidirectional: bool,
+    /// Area id of the connection, lower than 64.
+    pub area: u8,
+    /// Polygon flags of the connection, matched by the query filters.
+    pub flags: u16,
+    /// User defined identifier of the connection.
+    pub user_id: u32,
+}
+
+/// Off-mesh connections laid out in the separate buffers expected by Detour.
+#[derive(Debug, Default)]
+pub(crate) struct OffMeshConnectionBuffers {
+    vertices: Vec<f32>,
+    radii: Vec<f32>,
+    flags: Vec<u16>,
+    areas: Vec<u8>,
+    directions: Vec<u8>,
+    ids: Vec<u32>,
+}
+
+impl OffMeshConnectionBuffers {
+    /// Lay out the specified connections. Fails with a [`DetourStatus`] error if the area id of a
+    /// connection is not valid, which Detour would silently truncate.
+    pub fn new(connections: &[OffMeshConnection]) -> crate::Result<OffMeshConnectionBuffers> {
+        if connections.iter().any(|c| c.area >= MAX_AREAS) {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        Ok(OffMeshConnectionBuffers {
+            vertices: connections
+                .iter()
+                .flat_map(|c| c.start.into_iter().chain(c.end))
+                .collect(),
+            radii: connections.iter().map(|c| c.radius).collect(),
+            flags: connections.iter().map(|c| c.flags).collect(),
+            areas: connections.iter().map(|c| c.area).collect(),
+            directions: connections
+                .iter()
+                .map(|c| {
+                    if c.bidirectional {
+                        OFF_MESH_CON_BIDIR
+                    } else {
+                        0
+                    }
+                })
+                .collect(),
+            ids: connections.iter().map(|c| c.user_id).collect(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.radii.len()
+    }
+
+    /// Point the off-mesh connection fields of `params` to these buffers. The buffers must not be
+    /// moved or dropped while `params` is in use.
+    pub fn fill_params(&self, params: &mut NavMeshCreateParams) {
+        params.off_mesh_conn_vertices = self.vertices.as_ptr();
+        params.off_mesh_conn_radii = self.radii.as_ptr();
+        params.off_mesh_conn_flags = self.flags.as_ptr();
+        params.off_mesh_conn_areas = self.areas.as_ptr();
+        params.off_mesh_conn_dir = self.directions.as_ptr();
+        params.off_mesh_conn_ids = self.ids.as_ptr();
+        params.off_mesh_conn_count = self.len() as i32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OffMeshConnection, OffMeshConnectionBuffers};
+
+    #[test]
+    fn test_off_mesh_connection_buffers_layout() {
+        let connections = [
+            OffMeshConnection {
+                start: [0., 1., 2.],
+                end: [3., 4., 5.],
+                radius: 0.5,
+                bidirectional: true,
+                area: 2,
+                flags: 0x8,
+                user_id: 42,
+            },
+            OffMeshConnection {
+                start: [6., 7., 8.],
+                end: [9., 10., 11.],
+                radius: 1.,
+                bidirectional: false,
+                area: 3,
+                flags: 0x10,
+                user_id: 43,
+            },
+        ];
+        let buffers = OffMeshConnectionBuffers::new(&connections).unwrap();
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(
+            buffers.vertices,
+            (0..12).map(|v| v as f32).collect::<Vec<_>>()
+        );
+        assert_eq!(buffers.radii, [0.5, 1.]);
+        assert_eq!(buffers.flags, [0x8, 0x10]);
+        assert_eq!(buffers.areas, [2, 3]);
+        assert_eq!(buffers.directions, [1, 0]);
+        assert_eq!(buffers.ids, [42, 43]);
+    }
+
+    #[test]
+    fn test_off_mesh_connection_invalid_area() {
+        let connection = OffMeshConnection {
+            start: [0., 0., 0.],
+            end: [1., 0., 1.],
+            radius: 0.5,
+            bidirectional: true,
+            area: 64,
+            flags: 1,
+            user_id: 0,
+        };
+        assert!(OffMeshConnectionBuffers::new(&[connection]).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use recast_sys::ffi::detour::*;
+
+use super::{DetourStatus, Error, NavMeshQueryGuard, PathCorridorGuard, PolyRef};
+
+/// Result of [`PathCorridorGuard::move_over_offmesh_connection`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffMeshConnectionTraversal {
+    /// The polygon the connection is entered from.
+    pub from_poly: PolyRef,
+    /// The endpoint of the connection the agent enters it from.
+    pub start: [f32; 3],
+    /// The endpoint of the connection the agent leaves it from, which is the new position of the
+    /// corridor.
+    pub end: [f32; 3],
+}
+
+impl<'q> PathCorridorGuard<'q> {
+    /// Attempt to move the target of the corridor to the specified position. If the move was
+    /// successful, returns the new target, constrained to the surface of the navmesh.
+    ///
+    /// Fails with an invalid parameter error if `query` is not a query of the corridor navmesh.
+    pub fn move_target_position<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        new_target: [f32; 3],
+        query: &mut NavMeshQueryGuard,
+        filter: &F,
+    ) -> crate::Result<Option<[f32; 3]>> {
+        self.check_query(query)?;
+        let _lock = query.ptr.read().unwrap();
+        let res = unsafe {
+            self.path.pin_mut().move_target_position(
+                new_target.as_ptr(),
+                query.query.pin_mut().get_unchecked_mut() as *mut _,
+                filter.as_ref() as *const _,
+            )
+        };
+        if res {
+            Ok(Some(*self.target()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Shortcut the start of the corridor if `next`, usually the next corner of the path, is
+    /// visible from the current position within `path_optimization_range`.
+    ///
+    /// This is inexpensive, and can be performed every update.
+    ///
+    /// Fails with an invalid parameter error if `query` is not a query of the corridor navmesh.
+    pub fn optimize_path_visibility<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        next: [f32; 3],
+        path_optimization_range: f32,
+        query: &mut NavMeshQueryGuard,
+        filter: &F,
+    ) -> crate::Result<()> {
+        self.check_query(query)?;
+        let _lock = query.ptr.read().unwrap();
+        unsafe {
+            self.path.pin_mut().optimize_path_visibility(
+                next.as_ptr(),
+                path_optimization_range,
+                query.query.pin_mut().get_unchecked_mut() as *mut _,
+                filter.as_ref() as *const _,
+            )
+        }
+        Ok(())
+    }
+
+    /// Replan the start of the corridor with a local search, to account for changes of the navmesh
+    /// topology. Returns whether the corridor was changed.
+    ///
+    /// This is relatively expensive, and should only be performed every few updates.
+    ///
+    /// Fails with an invalid parameter error if `query` is not a query of the corridor navmesh.
+    pub fn optimize_path_topology<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        query: &mut NavMeshQueryGuard,
+        filter: &F,
+    ) -> crate::Result<bool> {
+        self.check_query(query)?;
+        let _lock = query.ptr.read().unwrap();
+        Ok(unsafe {
+            self.path.pin_mut().optimize_path_topology(
+                query.query.pin_mut().get_unchecked_mut() as *mut _,
+                filter.as_ref() as *const _,
+            )
+        })
+    }
+
+    /// Advance the corridor over the off-mesh connection `connection`, which must be in the
+    /// corridor. The position of the corridor is moved to the end of the connection.
+    ///
+    /// Returns `None` if the connection is not in the corridor.
+    ///
+    /// Fails with an invalid parameter error if `query` is not a query of the corridor navmesh.
+    pub fn move_over_offmesh_connection(
+        &mut self,
+        connection: PolyRef,
+        query: &mut NavMeshQueryGuard,
+    ) -> crate::Result<Option<OffMeshConnectionTraversal>> {
+        self.check_query(query)?;
+        let _lock = query.ptr.read().unwrap();
+        let mut refs = [dtPolyRef::default(); 2];
+        let mut start = [0.; 3];
+        let mut end = [0.; 3];
+        let res = unsafe {
+            self.path.pin_mut().move_over_offmesh_connection(
+                connection.to_ffi(),
+                refs.as_mut_ptr(),
+                start.as_mut_ptr(),
+                end.as_mut_ptr(),
+                query.query.pin_mut().get_unchecked_mut() as *mut _,
+            )
+        };
+        if !res {
+            return Ok(None);
+        }
+        Ok(
+            PolyRef::from_ffi(refs[0]).map(|from_poly| OffMeshConnectionTraversal {
+                from_poly,
+                start,
+                end,
+            }),
+        )
+    }
+
+    /// Make `safe_poly` the start of the corridor and `safe_position` its position, e.g. after an
+    /// agent was moved off the corridor. Returns whether the corridor was changed.
+    pub fn fix_path_start(&mut self, safe_poly: PolyRef, safe_position: [f32; 3]) -> bool {
+        unsafe {
+            self.path
+                .pin_mut()
+                .fix_path_start(safe_poly.to_ffi(), safe_position.as_ptr())
+        }
+    }
+
+    /// Cut the corridor at its first polygon which is no longer valid, or does not pass the filter.
+    /// If the first polygon is not valid, the corridor is reset to `safe_poly` and
+    /// `safe_position`.
+    ///
+    /// Fails with an invalid parameter error if `query` is not a query of the corridor navmesh.
+    pub fn trim_invalid_path<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        safe_poly: PolyRef,
+        safe_position: [f32; 3],
+        query: &mut NavMeshQueryGuard,
+        filter: &F,
+    ) -> crate::Result<bool> {
+        self.check_query(query)?;
+        let _lock = query.ptr.read().unwrap();
+        Ok(unsafe {
+            self.path.pin_mut().trim_invalid_path(
+                safe_poly.to_ffi(),
+                safe_position.as_ptr(),
+                query.query.pin_mut().get_unchecked_mut() as *mut _,
+                filter.as_ref() as *const _,
+            )
+        })
+    }
+
+    /// Return whether the first `max_look_ahead` polygons of the corridor are still valid, and
+    /// pass the filter.
+    ///
+    /// Fails with an invalid parameter error if `query` is not a query of the corridor navmesh.
+    pub fn is_valid<F: AsRef<dtQueryFilter>>(
+        &self,
+        max_look_ahead: u32,
+        query: &NavMeshQueryGuard,
+        filter: &F,
+    ) -> crate::Result<bool> {
+        self.check_query(query)?;
+        let _lock = query.ptr.read().unwrap();
+        Ok(unsafe {
+            path_corridor_is_valid(
+                self.path.as_ref(),
+                max_look_ahead as i32,
+                query.query.as_ref(),
+                filter.as_ref() as *const _,
+            )
+        })
+    }
+
+    /// Return the target of the path corridor.
+    pub fn target(&self) -> &[f32; 3] {
+        unsafe {
+            std::slice::from_raw_parts(self.path.as_ref().get_target(), 3)
+                .try_into()
+                .unwrap()
+        }
+    }
+
+    /// Return the polygons of the path corridor, from its position to its target.
+    pub fn path(&self) -> &[PolyRef] {
+        let path = self.path.as_ref();
+        let raw = crate::slice_from_raw_parts_or_dangling(path.get_path(), path.len() as usize);
+        PolyRef::from_ffi_slice(raw).expect("Null poly ref in the path corridor")
+    }
+
+    /// Return the polygon containing the position of the corridor, if the corridor is not empty.
+    pub fn first_poly(&self) -> Option<PolyRef> {
+        PolyRef::from_ffi(self.path.as_ref().get_first_poly())
+    }
+
+    /// Return the polygon containing the target of the corridor, if the corridor is not empty.
+    pub fn last_poly(&self) -> Option<PolyRef> {
+        PolyRef::from_ffi(self.path.as_ref().get_last_poly())
+    }
+
+    fn check_query(&self, query: &NavMeshQueryGuard) -> crate::Result<()> {
+        if !Arc::ptr_eq(&self.ptr, &query.ptr) {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_path_corridor() {
+        use crate::detour::{
+            test_navmesh_with_connections, OffMeshConnection, PolyType, QueryFilter,
+        };
+
+        let connection = OffMeshConnection {
+            start: [-5., 0., -5.],
+            end: [5., 0., 5.],
+            radius: 0.5,
+            bidirectional: true,
+            area: 63,
+            flags: 1,
+            user_id: 1,
+        };
+        let navmesh = test_navmesh_with_connections(&[connection]);
+        let connection_poly = {
+            let tiles = navmesh.tiles();
+            let tile = tiles.iter().next().unwrap();
+            let index = tile
+                .polys()
+                .position(|poly| poly.poly_type() == PolyType::OffMeshConnection)
+                .unwrap();
+            tiles.poly_ref(tile, index as u32).unwrap()
+        };
+
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+        let (start_poly, start) = query
+            .find_nearest_polygon([-5., 0., -5.], [1., 1., 1.], &filter)
+            .unwrap();
+        let (end_poly, end) = query
+            .find_nearest_polygon([8., 0., 8.], [1., 1., 1.], &filter)
+            .unwrap();
+        let path = [start_poly, connection_poly, end_poly];
+        let mut corridor = query.new_corridor(start, end, &path, &filter).unwrap();
+        let mut corridor = corridor.upgrade().unwrap();
+
+        assert_eq!(corridor.path(), path);
+        assert_eq!(corridor.first_poly(), Some(start_poly));
+        assert_eq!(corridor.last_poly(), Some(end_poly));
+        assert_eq!(*corridor.target(), end);
+        assert!(corridor.is_valid(8, &query, &filter).unwrap());
+        let corners = corridor.find_corners(4, &mut query, &filter).unwrap();
+        let (_, _, corner_polys) = corners.into_parts();
+        assert_eq!(corner_polys.last(), Some(&Some(connection_poly)));
+
+        let traversal = corridor
+            .move_over_offmesh_connection(connection_poly, &mut query)
+            .unwrap()
+            .unwrap();
+        assert_eq!(traversal.from_poly, start_poly);
+        assert!((traversal.start[0] + 5.).abs() < 0.5 && (traversal.start[2] + 5.).abs() < 0.5);
+        assert!((traversal.end[0] - 5.).abs() < 0.5 && (traversal.end[2] - 5.).abs() < 0.5);
+        assert_eq!(*corridor.position(), traversal.end);
+        assert_eq!(corridor.path(), [end_poly]);
+        assert!(corridor
+            .move_over_offmesh_connection(connection_poly, &mut query)
+            .unwrap()
+            .is_none());
+
+        let target = corridor
+            .move_target_position([6., 0., 6.], &mut query, &filter)
+            .unwrap()
+            .unwrap();
+        assert!((target[0] - 6.).abs() < 0.1 && (target[2] - 6.).abs() < 0.1);
+        let position = corridor
+            .move_position([5.5, 0., 5.5], &mut query, &filter)
+            .unwrap();
+        assert!((position[0] - 5.5).abs() < 0.1 && (position[2] - 5.5).abs() < 0.1);
+        corridor
+            .optimize_path_visibility(target, 10., &mut query, &filter)
+            .unwrap();
+        corridor
+            .optimize_path_topology(&mut query, &filter)
+            .unwrap();
+        assert!(corridor
+            .trim_invalid_path(end_poly, end, &mut query, &filter)
+            .unwrap());
+        assert!(corridor.fix_path_start(end_poly, end));
+        assert_eq!(corridor.first_poly(), Some(end_poly));
+
+        let other_navmesh = test_navmesh_with_connections(&[]);
+        let mut other_query = other_navmesh.new_query(256).unwrap();
+        let other_query = other_query.upgrade().unwrap();
+        assert!(corridor.is_valid(8, &other_query, &filter).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::{Arc, RwLock, Weak};
+
+use recast_sys::ffi::detour::*;
+
+use crate::uptr_wrapper;
+
+use super::{DetourStatus, Error, NavMesh, OtherError, OwnedNavMesh, PolyRef, QueryFilter};
+
+uptr_wrapper!(PathQueuePriv, dtPathQueue, new_path_queue);
+
+/// Identifier of a request of a [`PathQueue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PathRequestId(u32);
+
+/// Progress of a request of a [`PathQueue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathRequestStatus {
+    /// The request is waiting to be processed, or is being processed.
+    Pending,
+    /// A path was found, and can be retrieved with [`PathQueue::take_result`].
+    Done,
+    /// No path was found, or the request does not exist.
+    Failed,
+}
+
+/// A queue of path requests, processed over several calls to [`update`](Self::update) within an
+/// iteration budget.
+///
+/// The queue holds up to 8 requests at once. Completed requests are dropped if their result is
+/// not taken within two updates.
+///
+/// Like [`Crowd`](super::Crowd), the queue internally holds a weak pointer to its navmesh, and
+/// [`update`](Self::update) fails with an [`OtherError::DeallocatedResource`] error once it has
+/// been deallocated.
+pub struct PathQueue {
+    wptr: Weak<RwLock<OwnedNavMesh>>,
+    queue: PathQueuePriv,
+    max_path_size: u32,
+    // Detour keeps a pointer to the filter until the request is processed
+    filters: Vec<(PathRequestId, QueryFilter)>,
+}
+
+impl NavMesh {
+    /// Create a new path queue, whose paths go through a maximum of `max_path_size` polygons,
+    /// and whose searches use up to `max_search_nodes` nodes.
+    pub fn new_path_queue(
+        &self,
+        max_path_size: u32,
+        max_search_nodes: u32,
+    ) -> crate::Result<PathQueue> {
+        let mut queue = PathQueuePriv::new()?;
+        let lock = self.ptr.read().unwrap();
+        // The queue only uses the navmesh through const navmesh queries
+        let navmesh = lock.as_ref() as *const dtNavMesh as *mut dtNavMesh;
+        let res = unsafe {
+            queue
+                .pin_mut()
+                .init(max_path_size as i32, max_search_nodes as i32, navmesh)
+        };
+        if !res {
+            return Err(Error::Other(OtherError::MemAllocFailed))?;
+        }
+        Ok(PathQueue {
+            wptr: Arc::downgrade(&self.ptr),
+            queue,
+            max_path_size,
+            filters: Vec::new(),
+        })
+    }
+}
+
+impl PathQueue {
+    /// Queue a request for a path going from the `start` position lying in the `start_poly`
+    /// polygon, to the `end` position lying in the `end_poly` polygon. The filter is copied into
+    /// the queue.
+    ///
+    /// Fails with an [`OtherError::PathQueueFull`] error if the queue has no room for more
+    /// requests.
+    pub fn request(
+        &mut self,
+        start_poly: PolyRef,
+        end_poly: PolyRef,
+        start: [f32; 3],
+        end: [f32; 3],
+        filter: &QueryFilter,
+    ) -> crate::Result<PathRequestId> {
+        let filter = filter.clone();
+        let request = unsafe {
+            self.queue.pin_mut().request(
+                start_poly.to_ffi(),
+                end_poly.to_ffi(),
+                start.as_ptr(),
+                end.as_ptr(),
+                filter.as_ref() as *const _,
+            )
+        };
+        if request == 0 {
+            return Err(Error::Other(OtherError::PathQueueFull))?;
+        }
+        let id = PathRequestId(request);
+        self.filters.push((id, filter));
+        Ok(id)
+    }
+
+    /// Process the queued requests, performing up to `max_iters` search iterations.
+    pub fn update(&mut self, max_iters: u32) -> crate::Result<()> {
+        let navmesh = self
+            .wptr
+            .upgrade()
+            .ok_or(Error::Other(OtherError::DeallocatedResource))?;
+        let _lock = navmesh.read().unwrap();
+        self.queue.pin_mut().update(max_iters as i32);
+
+        let queue = self.queue.as_ref();
+        self.filters.retain(|(id, _)| {
+            let status: DetourStatus = queue.get_request_status(id.0).into();
+            !status.is_success() && !status.is_failure()
+        });
+        Ok(())
+    }
+
+    /// Return the progress of a request.
+    pub fn status(&self, id: PathRequestId) -> PathRequestStatus {
+        let status: DetourStatus = self.queue.as_ref().get_request_status(id.0).into();
+        if status.is_failure() {
+            PathRequestStatus::Failed
+        } else if status.is_success() {
+            PathRequestStatus::Done
+        } else {
+            PathRequestStatus::Pending
+        }
+    }
+
+    /// Return the path found by a completed request, and remove the request from the queue.
+    ///
+    /// If the destination could not be reached, the path leads to the polygon closest to it.
+    /// Fails with an [`OtherError::PathRequestPending`] error if the request has not been
+    /// processed yet, and with a [`DetourStatus`] error if it failed.
+    pub fn take_result(&mut self, id: PathRequestId) -> crate::Result<Vec<PolyRef>> {
+        let status: DetourStatus = self.queue.as_ref().get_request_status(id.0).into();
+        if !status.is_success() && !status.is_failure() {
+            return Err(Error::Other(OtherError::PathRequestPending))?;
+        }
+
+        let mut path = Vec::with_capacity(self.max_path_size as usize);
+        let mut path_len = 0;
+        let result_status: DetourStatus = unsafe {
+            let status = self.queue.pin_mut().get_path_result(
+                id.0,
+                path.as_mut_ptr(),
+                &mut path_len as *mut i32,
+                self.max_path_size as i32,
+            );
+            path.set_len(path_len as usize);
+            status.into()
+        };
+        self.filters.retain(|(filter_id, _)| *filter_id != id);
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        if result_status.is_failure() {
+            return Err(Error::from(result_status))?;
+        }
+        Ok(PolyRef::from_ffi_vec(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_path_queue() {
+        use super::PathRequestStatus;
+        use crate::detour::{test_navmesh, OtherError, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let filter = QueryFilter::default();
+        let (start_poly, start, end_poly, end, path) = {
+            let mut query = query.upgrade().unwrap();
+            let (start_poly, start) = query
+                .find_nearest_polygon([-8., 0., -8.], [1., 1., 1.], &filter)
+                .unwrap();
+            let (end_poly, end) = query
+                .find_nearest_polygon([8., 0., 8.], [1., 1., 1.], &filter)
+                .unwrap();
+            let path = query
+                .find_path(start, start_poly, end, end_poly, 64, &filter)
+                .unwrap();
+            (start_poly, start, end_poly, end, path)
+        };
+
+        let mut queue = navmesh.new_path_queue(64, 256).unwrap();
+        let ids: Vec<_> = (0..8)
+            .map(|_| {
+                queue
+                    .request(start_poly, end_poly, start, end, &filter)
+                    .unwrap()
+            })
+            .collect();
+        assert!(matches!(
+            queue.request(start_poly, end_poly, start, end, &filter),
+            Err(crate::Error::Detour(crate::detour::Error::Other(
+                OtherError::PathQueueFull
+            )))
+        ));
+        assert_eq!(queue.status(ids[0]), PathRequestStatus::Pending);
+        assert!(queue.take_result(ids[0]).is_err());
+
+        queue.update(1000).unwrap();
+        for &id in &ids {
+            assert_eq!(queue.status(id), PathRequestStatus::Done);
+            assert_eq!(queue.take_result(id).unwrap(), path);
+            assert_eq!(queue.status(id), PathRequestStatus::Failed);
+        }
+        assert!(queue.filters.is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+use super::{DetourStatus, Error, NavMesh, PolyRef, MAX_AREAS};
+
+impl NavMesh {
+    /// Set the flags of a polygon, e.g. to open or close a door by toggling a flag excluded by the
+    /// query filters.
+    ///
+    /// This method waits for the queries running on other threads to complete, see
+    /// [`NavMeshQueryGuard`](super::NavMeshQueryGuard).
+    pub fn set_poly_flags(&self, poly: PolyRef, flags: u16) -> crate::Result<()> {
+        let mut lock = self.ptr.write().unwrap();
+        let status: DetourStatus = lock.pin_mut().set_poly_flags(poly.to_ffi(), flags).into();
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(())
+    }
+
+    /// Return the flags of a polygon.
+    pub fn poly_flags(&self, poly: PolyRef) -> crate::Result<u16> {
+        let lock = self.ptr.read().unwrap();
+        let mut flags = 0;
+        let status: DetourStatus = unsafe {
+            lock.as_ref()
+                .get_poly_flags(poly.to_ffi(), &mut flags as *mut u16)
+                .into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(flags)
+    }
+
+    /// Set the area id of a polygon, e.g. to mark a temporary hazard given a high traversal cost
+    /// by the query filters.
+    ///
+    /// Fails with a [`DetourStatus`] error if `area` is not a valid area id, i.e. if it is greater
+    /// than 63. Like [`set_poly_flags`](Self::set_poly_flags), this method waits for the queries
+    /// running on other threads to complete.
+    pub fn set_poly_area(&self, poly: PolyRef, area: u8) -> crate::Result<()> {
+        if area >= MAX_AREAS {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        let mut lock = self.ptr.write().unwrap();
+        let status: DetourStatus = lock.pin_mut().set_poly_area(poly.to_ffi(), area).into();
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(())
+    }
+
+    /// Return the area id of a polygon.
+    pub fn poly_area(&self, poly: PolyRef) -> crate::Result<u8> {
+        let lock = self.ptr.read().unwrap();
+        let mut area = 0;
+        let status: DetourStatus = unsafe {
+            lock.as_ref()
+                .get_poly_area(poly.to_ffi(), &mut area as *mut u8)
+                .into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_poly_flags_and_area() {
+        use crate::detour::{test_navmesh, PolyRef, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut filter = QueryFilter::default();
+
+        let query = query.upgrade().unwrap();
+        let (poly, _) = query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .unwrap();
+
+        // The navmesh can be modified while a query guard is alive
+        navmesh.set_poly_flags(poly, 0x8).unwrap();
+        navmesh.set_poly_area(poly, 12).unwrap();
+        assert_eq!(navmesh.poly_flags(poly).unwrap(), 0x8);
+        assert_eq!(navmesh.poly_area(poly).unwrap(), 12);
+
+        filter.set_exclude_flags(0x8);
+        assert!(query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .is_err());
+
+        assert!(navmesh.set_poly_area(poly, 64).is_err());
+        assert_eq!(navmesh.poly_area(poly).unwrap(), 12);
+
+        let invalid = PolyRef::new(poly.get() + 1000).unwrap();
+        assert!(navmesh.poly_flags(invalid).is_err());
+    }
+}
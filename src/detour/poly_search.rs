@@ -0,0 +1,332 @@
+use recast_sys::{ffi::detour::*, PolyQueryCallback};
+
+use super::{DetourStatus, Error, NavMeshQueryGuard, PolyRef};
+
+/// A polygon reached by the [`find_polys_around_circle`](NavMeshQueryGuard::find_polys_around_circle)
+/// and [`find_polys_around_shape`](NavMeshQueryGuard::find_polys_around_shape) searches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolySearchResult {
+    /// Reference of the polygon.
+    pub poly: PolyRef,
+    /// Reference of the polygon the search reached this polygon from, or `None` for the start
+    /// polygon.
+    pub parent: Option<PolyRef>,
+    /// Accumulated cost of reaching the polygon from the start position.
+    pub cost: f32,
+}
+
+/// A polygon found by the [`find_local_neighbourhood`](NavMeshQueryGuard::find_local_neighbourhood)
+/// search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeighbourPoly {
+    /// Reference of the polygon.
+    pub poly: PolyRef,
+    /// Reference of the polygon the search reached this polygon from, or `None` for the start
+    /// polygon.
+    pub parent: Option<PolyRef>,
+}
+
+impl<'q> NavMeshQueryGuard<'q> {
+    /// Find up to `max_results` polygons touching the circle of radius `radius` around `center`,
+    /// exploring the navmesh from the `start_poly` polygon, which contains `center`.
+    ///
+    /// The polygons are returned in the order they are reached by the search, along with the cost
+    /// of reaching them.
+    pub fn find_polys_around_circle<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        start_poly: PolyRef,
+        center: [f32; 3],
+        radius: f32,
+        filter: &F,
+        max_results: u32,
+    ) -> crate::Result<Vec<PolySearchResult>> {
+        self.poly_search(max_results, |refs, parents, costs, count| unsafe {
+            self.query.as_ref().find_polys_around_circle(
+                start_poly.to_ffi(),
+                center.as_ptr(),
+                radius,
+                filter.as_ref() as *const _,
+                refs,
+                parents,
+                costs,
+                count,
+                max_results as i32,
+            )
+        })
+    }
+
+    /// Find up to `max_results` polygons touching the convex polygon `shape`, exploring the
+    /// navmesh from the `start_poly` polygon, which must contain the centroid of the shape.
+    ///
+    /// The polygons are returned in the order they are reached by the search, along with the cost
+    /// of reaching them from the centroid of the shape. Fails with an invalid parameter error if
+    /// the shape has fewer than 3 vertices.
+    pub fn find_polys_around_shape<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        start_poly: PolyRef,
+        shape: &[[f32; 3]],
+        filter: &F,
+        max_results: u32,
+    ) -> crate::Result<Vec<PolySearchResult>> {
+        if shape.len() < 3 {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        self.poly_search(max_results, |refs, parents, costs, count| unsafe {
+            self.query.as_ref().find_polys_around_shape(
+                start_poly.to_ffi(),
+                shape.as_ptr() as *const f32,
+                shape.len() as i32,
+                filter.as_ref() as *const _,
+                refs,
+                parents,
+                costs,
+                count,
+                max_results as i32,
+            )
+        })
+    }
+
+    /// Find up to `max_results` non-overlapping polygons in the circle of radius `radius` around
+    /// `center`, exploring the navmesh from the `start_poly` polygon, which contains `center`.
+    ///
+    /// This is meant for local steering: unlike [`find_polys_around_circle`](Self::find_polys_around_circle),
+    /// polygons overlapping the ones already found (e.g. on another floor) are skipped.
+    pub fn find_local_neighbourhood<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        start_poly: PolyRef,
+        center: [f32; 3],
+        radius: f32,
+        filter: &F,
+        max_results: u32,
+    ) -> crate::Result<Vec<NeighbourPoly>> {
+        let _lock = self.ptr.read().unwrap();
+        let mut refs = Vec::with_capacity(max_results as usize);
+        let mut parents = Vec::with_capacity(max_results as usize);
+        let mut count = 0;
+        let status: DetourStatus = unsafe {
+            let status = self.query.as_ref().find_local_neighbourhood(
+                start_poly.to_ffi(),
+                center.as_ptr(),
+                radius,
+                filter.as_ref() as *const _,
+                refs.as_mut_ptr(),
+                parents.as_mut_ptr(),
+                &mut count as *mut i32,
+                max_results as i32,
+            );
+            refs.set_len(count as usize);
+            parents.set_len(count as usize);
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(refs
+            .into_iter()
+            .zip(parents)
+            .filter_map(|(poly, parent)| {
+                Some(NeighbourPoly {
+                    poly: PolyRef::from_ffi(poly)?,
+                    parent: PolyRef::from_ffi(parent),
+                })
+            })
+            .collect())
+    }
+
+    /// Find up to `max_polys` polygons overlapping the box of half extents `half_extents` around
+    /// `center`.
+    ///
+    /// The polygons are returned in no particular order. See
+    /// [`for_each_polygon_in_box`](Self::for_each_polygon_in_box) to process all of them without
+    /// a size limit.
+    pub fn query_polygons<F: AsRef<dtQueryFilter>>(
+        &self,
+        center: [f32; 3],
+        half_extents: [f32; 3],
+        filter: &F,
+        max_polys: u32,
+    ) -> crate::Result<Vec<PolyRef>> {
+        let _lock = self.ptr.read().unwrap();
+        let mut polys = Vec::with_capacity(max_polys as usize);
+        let mut count = 0;
+        let status: DetourStatus = unsafe {
+            let status = self.query.as_ref().query_polygons(
+                center.as_ptr(),
+                half_extents.as_ptr(),
+                filter.as_ref() as *const _,
+                polys.as_mut_ptr(),
+                &mut count as *mut i32,
+                max_polys as i32,
+            );
+            polys.set_len(count as usize);
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(PolyRef::from_ffi_vec(polys))
+    }
+
+    /// Call `callback` on all the polygons overlapping the box of half extents `half_extents`
+    /// around `center`.
+    ///
+    /// The polygons are passed to the callback in batches, so it is usually called several times.
+    ///
+    /// # Panics
+    ///
+    /// The callback is called from Detour, which a panic cannot unwind through: if `callback`
+    /// panics, the process is aborted.
+    pub fn for_each_polygon_in_box<F, C>(
+        &self,
+        center: [f32; 3],
+        half_extents: [f32; 3],
+        filter: &F,
+        mut callback: C,
+    ) -> crate::Result<()>
+    where
+        F: AsRef<dtQueryFilter>,
+        C: FnMut(&[PolyRef]),
+    {
+        let _lock = self.ptr.read().unwrap();
+        let mut process = |polys: &[dtPolyRef]| match PolyRef::from_ffi_slice(polys) {
+            Some(polys) => callback(polys),
+            None => callback(&PolyRef::from_ffi_vec(polys.to_vec())),
+        };
+        let mut callback = PolyQueryCallback(&mut process);
+        let status: DetourStatus = unsafe {
+            query_polygons_callback(
+                self.query.as_ref(),
+                center.as_ptr(),
+                half_extents.as_ptr(),
+                filter.as_ref() as *const _,
+                &mut callback,
+            )
+            .into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(())
+    }
+
+    fn poly_search<S>(&self, max_results: u32, search: S) -> crate::Result<Vec<PolySearchResult>>
+    where
+        S: FnOnce(*mut dtPolyRef, *mut dtPolyRef, *mut f32, *mut i32) -> u32,
+    {
+        let _lock = self.ptr.read().unwrap();
+        let mut refs = Vec::with_capacity(max_results as usize);
+        let mut parents = Vec::with_capacity(max_results as usize);
+        let mut costs = Vec::with_capacity(max_results as usize);
+        let mut count = 0;
+        let status: DetourStatus = unsafe {
+            let status = search(
+                refs.as_mut_ptr(),
+                parents.as_mut_ptr(),
+                costs.as_mut_ptr(),
+                &mut count as *mut i32,
+            );
+            refs.set_len(count as usize);
+            parents.set_len(count as usize);
+            costs.set_len(count as usize);
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(refs
+            .into_iter()
+            .zip(parents)
+            .zip(costs)
+            .filter_map(|((poly, parent), cost)| {
+                Some(PolySearchResult {
+                    poly: PolyRef::from_ffi(poly)?,
+                    parent: PolyRef::from_ffi(parent),
+                    cost,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_poly_searches() {
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let (start_poly, center) = query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .unwrap();
+
+        let around_circle = query
+            .find_polys_around_circle(start_poly, center, 20., &filter, 64)
+            .unwrap();
+        assert_eq!(around_circle[0].poly, start_poly);
+        assert_eq!(around_circle[0].parent, None);
+        assert_eq!(around_circle[0].cost, 0.);
+        assert!(around_circle[1..]
+            .iter()
+            .all(|result| result.parent.is_some() && result.cost > 0.));
+
+        let shape = [
+            [center[0] - 20., center[1], center[2] - 20.],
+            [center[0] - 20., center[1], center[2] + 20.],
+            [center[0] + 20., center[1], center[2] + 20.],
+            [center[0] + 20., center[1], center[2] - 20.],
+        ];
+        let around_shape = query
+            .find_polys_around_shape(start_poly, &shape, &filter, 64)
+            .unwrap();
+        assert_eq!(around_shape.len(), around_circle.len());
+        assert!(matches!(
+            query.find_polys_around_shape(start_poly, &shape[..2], &filter, 64),
+            Err(crate::Error::Detour(crate::detour::Error::Detour(Some(status))))
+                if status.is_invalid_param()
+        ));
+
+        let neighbourhood = query
+            .find_local_neighbourhood(start_poly, center, 20., &filter, 64)
+            .unwrap();
+        assert_eq!(neighbourhood[0].poly, start_poly);
+        assert_eq!(neighbourhood.len(), around_circle.len());
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_query_polygons() {
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let mut polys = query
+            .query_polygons([0., 0., 0.], [20., 1., 20.], &filter, 64)
+            .unwrap();
+        assert!(!polys.is_empty());
+
+        let mut visited = Vec::new();
+        query
+            .for_each_polygon_in_box([0., 0., 0.], [20., 1., 20.], &filter, |batch| {
+                visited.extend_from_slice(batch)
+            })
+            .unwrap();
+        polys.sort_unstable();
+        visited.sort_unstable();
+        assert_eq!(polys, visited);
+
+        let truncated = query
+            .query_polygons([0., 0., 0.], [20., 1., 20.], &filter, 1)
+            .unwrap();
+        assert_eq!(truncated.len(), 1);
+    }
+}
@@ -0,0 +1,115 @@
+use recast_sys::ffi::detour::*;
+
+use crate::uptr_wrapper;
+
+use super::{DetourStatus, Error, OtherError};
+
+uptr_wrapper!(ProximityGridPriv, dtProximityGrid, new_proximity_grid);
+
+/// A spatial hash grid of items identified by a `u16`, used by the crowd to find the neighbours
+/// of its agents.
+///
+/// Items are inserted with a 2D bounding box, usually on the XZ plane, into every grid cell the
+/// box overlaps. The grid is meant to be cleared and refilled every frame.
+pub struct ProximityGrid {
+    grid: ProximityGridPriv,
+    pool_size: u32,
+}
+
+impl ProximityGrid {
+    /// Create a new grid with square cells of size `cell_size`, and room for `pool_size` cell
+    /// entries.
+    ///
+    /// Fails with a [`DetourStatus`] error if `pool_size` is 0 or not lower than `0xffff`, the
+    /// limit of the `u16` entry indexes, or if `cell_size` is not a positive finite number.
+    pub fn new(pool_size: u32, cell_size: f32) -> crate::Result<ProximityGrid> {
+        let valid_cell_size = cell_size > 0. && cell_size.is_finite();
+        if !(1..0xffff).contains(&pool_size) || !valid_cell_size {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+        let mut grid = ProximityGridPriv::new()?;
+        if !grid.pin_mut().init(pool_size as i32, cell_size) {
+            return Err(Error::Other(OtherError::MemAllocFailed))?;
+        }
+        Ok(ProximityGrid { grid, pool_size })
+    }
+
+    /// Return the size of the grid cells.
+    pub fn cell_size(&self) -> f32 {
+        self.grid.as_ref().get_cell_size()
+    }
+
+    /// Remove all the items of the grid.
+    pub fn clear(&mut self) {
+        self.grid.pin_mut().clear();
+    }
+
+    /// Add an item to every cell overlapped by the bounding box from `bmin` to `bmax`.
+    ///
+    /// Each overlapped cell uses an entry of the pool, and the item is silently left out of the
+    /// remaining cells once the pool is exhausted.
+    pub fn add_item(&mut self, id: u16, bmin: [f32; 2], bmax: [f32; 2]) {
+        self.grid
+            .pin_mut()
+            .add_item(id, bmin[0], bmin[1], bmax[0], bmax[1]);
+    }
+
+    /// Return the ids of the items in the cells overlapped by the bounding box from `bmin` to
+    /// `bmax`, without duplicates.
+    ///
+    /// Items are found per cell, so the result may contain items whose own bounding box does not
+    /// overlap the queried one.
+    pub fn query_items(&self, bmin: [f32; 2], bmax: [f32; 2]) -> Vec<u16> {
+        let mut ids = Vec::with_capacity(self.pool_size as usize);
+        unsafe {
+            let count = self.grid.as_ref().query_items(
+                bmin[0],
+                bmin[1],
+                bmax[0],
+                bmax[1],
+                ids.as_mut_ptr(),
+                self.pool_size as i32,
+            );
+            ids.set_len(count as usize);
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProximityGrid;
+
+    #[test]
+    fn test_proximity_grid() {
+        let mut grid = ProximityGrid::new(64, 1.).unwrap();
+        assert_eq!(grid.cell_size(), 1.);
+
+        grid.add_item(1, [0.2, 0.2], [0.8, 0.8]);
+        grid.add_item(2, [0.5, 0.5], [2.5, 0.8]);
+        grid.add_item(3, [10.2, 10.2], [10.8, 10.8]);
+
+        let mut ids = grid.query_items([0., 0.], [0.9, 0.9]);
+        ids.sort();
+        assert_eq!(ids, [1, 2]);
+        assert_eq!(grid.query_items([2.1, 0.1], [2.2, 0.2]), [2]);
+        assert_eq!(grid.query_items([9.5, 9.5], [11., 11.]), [3]);
+        assert!(grid.query_items([5., 5.], [6., 6.]).is_empty());
+
+        grid.clear();
+        assert!(grid.query_items([0., 0.], [11., 11.]).is_empty());
+    }
+
+    #[test]
+    fn test_proximity_grid_invalid_params() {
+        assert!(ProximityGrid::new(0, 1.).is_err());
+        assert!(ProximityGrid::new(0xffff, 1.).is_err());
+        assert!(ProximityGrid::new(0xfffe, 1.).is_ok());
+        assert!(ProximityGrid::new(64, 0.).is_err());
+        assert!(ProximityGrid::new(64, -1.).is_err());
+        assert!(ProximityGrid::new(64, f32::NAN).is_err());
+        assert!(ProximityGrid::new(64, f32::INFINITY).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+use cxx::UniquePtr;
+use recast_sys::ffi::detour::*;
+use recast_sys::{QueryFilterCallbacks, QueryFilterPoly, RustQueryFilterCallbacks};
+
+use crate::{
+    check_uptr_alloc,
+    detour::{MeshTile, Poly, PolyRef, MAX_AREAS},
+    uptr_wrapper,
+};
+
+uptr_wrapper!(
+    /// Query filters define which polygons of the navmesh can be traversed by the navmesh
+    /// queries, and how much it costs to do so.
+    ///
+    /// A polygon passes the filter if it has at least one of the include flags, and none of the
+    /// exclude flags. The cost of moving across a polygon is the distance travelled, multiplied by
+    /// the cost of the area of the polygon.
+    ///
+    /// The default filter includes all flags, excludes none, and has a cost of `1.0` for all
+    /// areas.
+    pub QueryFilter,
+    dtQueryFilter,
+    new_query_filter
+);
+
+impl QueryFilter {
+    /// Return the flags a polygon must have at least one of to pass the filter.
+    pub fn include_flags(&self) -> u16 {
+        self.as_ref().get_include_flags()
+    }
+
+    /// Set the flags a polygon must have at least one of to pass the filter.
+    pub fn set_include_flags(&mut self, flags: u16) {
+        self.pin_mut().set_include_flags(flags);
+    }
+
+    /// Return the flags a polygon must have none of to pass the filter.
+    pub fn exclude_flags(&self) -> u16 {
+        self.as_ref().get_exclude_flags()
+    }
+
+    /// Set the flags a polygon must have none of to pass the filter.
+    pub fn set_exclude_flags(&mut self, flags: u16) {
+        self.pin_mut().set_exclude_flags(flags);
+    }
+
+    /// Return the traversal cost multiplier of the specified area.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `area` is not a valid area id, i.e. if it is greater than 63.
+    pub fn area_cost(&self, area: u8) -> f32 {
+        assert!(area < MAX_AREAS, "Invalid area id {}", area);
+        unsafe { self.as_ref().get_area_cost(area as i32) }
+    }
+
+    /// Set the traversal cost multiplier of the specified area.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `area` is not a valid area id, i.e. if it is greater than 63.
+    pub fn set_area_cost(&mut self, area: u8, cost: f32) {
+        assert!(area < MAX_AREAS, "Invalid area id {}", area);
+        unsafe { self.pin_mut().set_area_cost(area as i32, cost) }
+    }
+}
+
+impl Clone for QueryFilter {
+    fn clone(&self) -> Self {
+        let mut filter = QueryFilter::default();
+        filter.set_include_flags(self.include_flags());
+        filter.set_exclude_flags(self.exclude_flags());
+        for area in 0..MAX_AREAS {
+            filter.set_area_cost(area, self.area_cost(area));
+        }
+        filter
+    }
+}
+
+/// A polygon traversed by a path, as passed to [`QueryFilterCallback::get_cost`].
+#[derive(Clone, Copy)]
+pub struct TraversedPoly<'a> {
+    /// Reference of the polygon.
+    pub poly_ref: PolyRef,
+    /// Tile containing the polygon.
+    pub tile: MeshTile<'a>,
+    /// The polygon itself.
+    pub poly: Poly<'a>,
+}
+
+impl<'a> From<QueryFilterPoly<'a>> for TraversedPoly<'a> {
+    fn from((poly_ref, tile, poly): QueryFilterPoly<'a>) -> Self {
+        TraversedPoly {
+            poly_ref: PolyRef::from_ffi(poly_ref)
+                .expect("Null poly ref passed to the query filter"),
+            tile: MeshTile::new(tile),
+            poly: Poly::new(poly),
+        }
+    }
+}
+
+/// Custom polygon filtering and cost computation logic for the navmesh queries, see
+/// [`CallbackQueryFilter`].
+///
+/// The callbacks replace the flags and area costs logic of [`QueryFilter`] entirely.
+///
+/// # Panics
+///
+/// The callbacks are called from Detour, which a panic cannot unwind through: if one of them
+/// panics, the process is aborted.
+pub trait QueryFilterCallback: Send + Sync + 'static {
+    /// Return whether the polygon can be traversed. The default implementation accepts all
+    /// polygons.
+    fn pass_filter(&self, poly_ref: PolyRef, tile: MeshTile, poly: Poly) -> bool {
+        let _ = (poly_ref, tile, poly);
+        true
+    }
+
+    /// Return the cost of moving from `start` to `end`, both points lying on the edges of the
+    /// `current` polygon. `prev` and `next` are the polygons the path comes from and goes to, if
+    /// any. The default implementation returns the distance between the two points.
+    fn get_cost(
+        &self,
+        start: [f32; 3],
+        end: [f32; 3],
+        prev: Option<TraversedPoly>,
+        current: TraversedPoly,
+        next: Option<TraversedPoly>,
+    ) -> f32 {
+        let _ = (prev, current, next);
+        start
+            .iter()
+            .zip(end)
+            .map(|(s, e)| (e - s) * (e - s))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/// Adapter from the public [`QueryFilterCallback`] trait to the one expected by `recast-sys`.
+struct CallbackAdapter<C>(C);
+
+impl<C: QueryFilterCallback> QueryFilterCallbacks for CallbackAdapter<C> {
+    fn pass_filter(&self, (poly_ref, tile, poly): QueryFilterPoly) -> bool {
+        let poly_ref =
+            PolyRef::from_ffi(poly_ref).expect("Null poly ref passed to the query filter");
+        self.0
+            .pass_filter(poly_ref, MeshTile::new(tile), Poly::new(poly))
+    }
+
+    fn get_cost(
+        &self,
+        pa: &[f32; 3],
+        pb: &[f32; 3],
+        prev: Option<QueryFilterPoly>,
+        cur: QueryFilterPoly,
+        next: Option<QueryFilterPoly>,
+    ) -> f32 {
+        self.0.get_cost(
+            *pa,
+            *pb,
+            prev.map(Into::into),
+            cur.into(),
+            next.map(Into::into),
+        )
+    }
+}
+
+/// Query filter implemented in Rust through a [`QueryFilterCallback`].
+///
+/// Can be used in place of a [`QueryFilter`] in all the navmesh queries.
+pub struct CallbackQueryFilter {
+    ptr: UniquePtr<RustQueryFilter>,
+}
+
+impl CallbackQueryFilter {
+    /// Create a query filter delegating its logic to `callback`.
+    pub fn new<C: QueryFilterCallback>(callback: C) -> crate::Result<CallbackQueryFilter> {
+        let callbacks = Box::new(RustQueryFilterCallbacks(Box::new(CallbackAdapter(
+            callback,
+        ))));
+        let ptr = check_uptr_alloc(new_rust_query_filter(callbacks))?;
+        Ok(CallbackQueryFilter { ptr })
+    }
+}
+
+impl AsRef<dtQueryFilter> for CallbackQueryFilter {
+    fn as_ref(&self) -> &dtQueryFilter {
+        rust_query_filter_as_base(self.ptr.as_ref().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryFilter;
+
+    #[test]
+    fn test_default_query_filter() {
+        let filter = QueryFilter::new().unwrap();
+        assert_eq!(filter.include_flags(), 0xffff);
+        assert_eq!(filter.exclude_flags(), 0);
+        assert!((0..64).all(|area| filter.area_cost(area) == 1.));
+    }
+
+    #[test]
+    fn test_query_filter_setters() {
+        let mut filter = QueryFilter::new().unwrap();
+        filter.set_include_flags(0x3);
+        filter.set_exclude_flags(0x4);
+        filter.set_area_cost(5, 10.);
+        assert_eq!(filter.include_flags(), 0x3);
+        assert_eq!(filter.exclude_flags(), 0x4);
+        assert_eq!(filter.area_cost(5), 10.);
+        assert_eq!(filter.area_cost(4), 1.);
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_callback_query_filter() {
+        use super::{CallbackQueryFilter, QueryFilterCallback};
+        use crate::detour::{test_navmesh, MeshTile, Poly, PolyRef, PolyType};
+        use crate::recast::WALKABLE_AREA;
+
+        struct AreaFilter(u8);
+
+        impl QueryFilterCallback for AreaFilter {
+            fn pass_filter(&self, _poly_ref: PolyRef, tile: MeshTile, poly: Poly) -> bool {
+                assert!(tile.header().is_some());
+                assert_eq!(poly.poly_type(), PolyType::Ground);
+                poly.area() == self.0
+            }
+        }
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+
+        let walkable = CallbackQueryFilter::new(AreaFilter(WALKABLE_AREA)).unwrap();
+        let path = query
+            .find_path_search_polys([-8., 0., -8.], [8., 0., 8.], [1., 1., 1.], 64, &walkable)
+            .unwrap();
+        assert!(!path.is_empty());
+
+        let nothing = CallbackQueryFilter::new(AreaFilter(1)).unwrap();
+        assert!(query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &nothing)
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_query_filter_invalid_area() {
+        let filter = QueryFilter::new().unwrap();
+        filter.area_cost(64);
+    }
+}
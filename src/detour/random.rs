@@ -0,0 +1,133 @@
+use rand::Rng;
+use recast_sys::{ffi::detour::*, RandomSource};
+
+use super::{DetourStatus, Error, NavMeshQueryGuard, OtherError, PolyRef};
+
+impl<'q> NavMeshQueryGuard<'q> {
+    /// Return a random point of the navmesh, along with the polygon containing it.
+    ///
+    /// Polygons passing the filter are picked with a probability proportional to their area.
+    /// Using a seeded `rng` makes the result reproducible.
+    ///
+    /// # Panics
+    ///
+    /// `rng` is called from Detour, which a panic cannot unwind through: if it panics, the
+    /// process is aborted.
+    pub fn random_point<F: AsRef<dtQueryFilter>, R: Rng + ?Sized>(
+        &self,
+        filter: &F,
+        rng: &mut R,
+    ) -> crate::Result<(PolyRef, [f32; 3])> {
+        let _lock = self.ptr.read().unwrap();
+        let mut next_f32 = || rng.random::<f32>();
+        let mut source = RandomSource(&mut next_f32);
+        let mut random_ref = dtPolyRef::default();
+        let mut random_point = [0.; 3];
+        let status: DetourStatus = unsafe {
+            find_random_point(
+                self.query.as_ref(),
+                filter.as_ref() as *const _,
+                &mut source,
+                &mut random_ref as *mut dtPolyRef,
+                random_point.as_mut_ptr(),
+            )
+            .into()
+        };
+        Self::random_point_result(status, random_ref, random_point)
+    }
+
+    /// Return a random point of the navmesh reachable from the `center` position, lying in the
+    /// `start_poly` polygon, along with the polygon containing it.
+    ///
+    /// The polygons are explored starting from `start_poly`, and only those overlapping the
+    /// circle of radius `radius` around `center` can be picked, with a probability proportional
+    /// to their area. The point itself may lie slightly outside of the circle.
+    ///
+    /// # Panics
+    ///
+    /// `rng` is called from Detour, which a panic cannot unwind through: if it panics, the
+    /// process is aborted.
+    pub fn random_point_around_circle<F: AsRef<dtQueryFilter>, R: Rng + ?Sized>(
+        &mut self,
+        start_poly: PolyRef,
+        center: [f32; 3],
+        radius: f32,
+        filter: &F,
+        rng: &mut R,
+    ) -> crate::Result<(PolyRef, [f32; 3])> {
+        let _lock = self.ptr.read().unwrap();
+        let mut next_f32 = || rng.random::<f32>();
+        let mut source = RandomSource(&mut next_f32);
+        let mut random_ref = dtPolyRef::default();
+        let mut random_point = [0.; 3];
+        let status: DetourStatus = unsafe {
+            find_random_point_around_circle(
+                self.query.as_ref(),
+                start_poly.to_ffi(),
+                center.as_ptr(),
+                radius,
+                filter.as_ref() as *const _,
+                &mut source,
+                &mut random_ref as *mut dtPolyRef,
+                random_point.as_mut_ptr(),
+            )
+            .into()
+        };
+        Self::random_point_result(status, random_ref, random_point)
+    }
+
+    fn random_point_result(
+        status: DetourStatus,
+        random_ref: dtPolyRef,
+        random_point: [f32; 3],
+    ) -> crate::Result<(PolyRef, [f32; 3])> {
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        match PolyRef::from_ffi(random_ref) {
+            Some(random_ref) => Ok((random_ref, random_point)),
+            None => Err(Error::from(OtherError::NoPolyFound))?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_random_points() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let points: Vec<_> = (0..16)
+            .map(|_| query.random_point(&filter, &mut rng).unwrap())
+            .collect();
+        assert!(points
+            .iter()
+            .all(|(_, p)| p[0].abs() <= 10. && p[2].abs() <= 10.));
+
+        // The same seed yields the same points
+        let mut rng = StdRng::seed_from_u64(42);
+        for point in &points {
+            assert_eq!(&query.random_point(&filter, &mut rng).unwrap(), point);
+        }
+
+        let (start_poly, center) = query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .unwrap();
+        for _ in 0..16 {
+            let (poly, point) = query
+                .random_point_around_circle(start_poly, center, 2., &filter, &mut rng)
+                .unwrap();
+            assert!(navmesh.is_valid_poly_ref(poly));
+            assert!(point[0].abs() <= 10. && point[2].abs() <= 10.);
+        }
+    }
+}
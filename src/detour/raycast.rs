@@ -0,0 +1,143 @@
+use recast_sys::ffi::detour::*;
+
+use super::{DetourStatus, Error, NavMeshQueryGuard, PolyRef};
+
+/// Raycast option computing the cost of the path along the ray (`DT_RAYCAST_USE_COSTS`).
+const RAYCAST_USE_COSTS: u32 = 1;
+
+/// Options of the [`NavMeshQueryGuard::raycast`] query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RaycastOptions {
+    /// Compute the cost of the path along the ray, see [`RaycastHit::path_cost`].
+    pub use_costs: bool,
+}
+
+impl RaycastOptions {
+    fn bits(&self) -> u32 {
+        if self.use_costs {
+            RAYCAST_USE_COSTS
+        } else {
+            0
+        }
+    }
+}
+
+/// Result of a [`NavMeshQueryGuard::raycast`] query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaycastHit {
+    /// The hit parameter, i.e. the position of the wall hit along the ray, as a fraction of the
+    /// ray length. Equal to `f32::MAX` if the ray reached its end without hitting a wall.
+    pub t: f32,
+    /// The normal of the wall hit, if any.
+    pub hit_normal: [f32; 3],
+    /// The index of the edge of the last visited polygon where the wall was hit, if any.
+    pub hit_edge_index: Option<u32>,
+    /// The polygons visited by the ray.
+    pub path: Vec<PolyRef>,
+    /// The cost of the path until the hit, if [`RaycastOptions::use_costs`] was set.
+    pub path_cost: Option<f32>,
+}
+
+impl RaycastHit {
+    /// Return whether the ray hit a wall before reaching its end.
+    pub fn is_hit(&self) -> bool {
+        self.t != f32::MAX
+    }
+
+    /// Return the position of the wall hit along the ray going from `start` to `end`, if any.
+    pub fn hit_position(&self, start: [f32; 3], end: [f32; 3]) -> Option<[f32; 3]> {
+        if !self.is_hit() {
+            return None;
+        }
+        Some([0, 1, 2].map(|i| start[i] + (end[i] - start[i]) * self.t))
+    }
+}
+
+impl<'q> NavMeshQueryGuard<'q> {
+    /// Cast a 'walkability' ray along the surface of the navmesh, from the `start` position
+    /// lying in the `start_poly` polygon towards the `end` position. The ray stops at the first
+    /// wall hit, and records up to `max_path` visited polygons.
+    ///
+    /// This is useful for line of sight checks and path shortcutting. The ray is cast in 2D on
+    /// the XZ plane, so the result is not reliable on navmeshes with overlapping floors.
+    pub fn raycast<F: AsRef<dtQueryFilter>>(
+        &self,
+        start_poly: PolyRef,
+        start: [f32; 3],
+        end: [f32; 3],
+        filter: &F,
+        options: RaycastOptions,
+        max_path: u32,
+    ) -> crate::Result<RaycastHit> {
+        let _lock = self.ptr.read().unwrap();
+        let mut path = Vec::with_capacity(max_path as usize);
+        let mut hit = RaycastHitData::default();
+        let status: DetourStatus = unsafe {
+            let status = raycast(
+                self.query.as_ref(),
+                start_poly.to_ffi(),
+                start.as_ptr(),
+                end.as_ptr(),
+                filter.as_ref() as *const _,
+                options.bits(),
+                dtPolyRef::default(),
+                path.as_mut_ptr(),
+                max_path as i32,
+                &mut hit,
+            );
+            path.set_len(hit.path_count as usize);
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+
+        let is_hit = hit.t != f32::MAX;
+        Ok(RaycastHit {
+            t: hit.t,
+            hit_normal: hit.hit_normal,
+            hit_edge_index: is_hit.then_some(hit.hit_edge_index as u32),
+            path: PolyRef::from_ffi_vec(path),
+            path_cost: options.use_costs.then_some(hit.path_cost),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_raycast() {
+        use super::RaycastOptions;
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let (start_poly, start) = query
+            .find_nearest_polygon([0., 0., 0.], [1., 1., 1.], &filter)
+            .unwrap();
+        let options = RaycastOptions { use_costs: true };
+
+        let hit = query
+            .raycast(start_poly, start, [2., 0., 2.], &filter, options, 16)
+            .unwrap();
+        assert!(!hit.is_hit());
+        assert!(hit.hit_edge_index.is_none());
+        assert!(!hit.path.is_empty());
+        assert!(hit.path_cost.unwrap() > 0.);
+
+        let end = [0., start[1], 20.];
+        let hit = query
+            .raycast(start_poly, start, end, &filter, options, 16)
+            .unwrap();
+        assert!(hit.is_hit());
+        assert!(hit.t > 0. && hit.t < 1.);
+        assert!(hit.hit_edge_index.is_some());
+        let position = hit.hit_position(start, end).unwrap();
+        assert!(position[2] > 5. && position[2] < 10.);
+        assert!(hit.hit_normal[2] < 0.);
+    }
+}
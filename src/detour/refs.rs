@@ -0,0 +1,183 @@
+use std::num::NonZero;
+
+pub use recast_sys::RawPolyRef;
+use recast_sys::{dtPolyRef, dtTileRef};
+
+use super::NavMesh;
+
+/// Reference to a polygon of a navmesh.
+///
+/// Poly refs encode the index of the tile containing the polygon, the index of the polygon in
+/// that tile, and the salt of the tile, which is used to detect refs to tiles which have been
+/// removed. Detour uses `0` as the null reference, so `Option<PolyRef>` is used where a
+/// reference may be missing.
+///
+/// References are 32 bit wide by default, and 64 bit wide with the `polyref64` feature, which
+/// allows for more tiles and polygons per tile.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PolyRef(NonZero<RawPolyRef>);
+
+/// Reference to a tile of a navmesh. Like [`PolyRef`], it contains a salt used to detect refs to
+/// removed tiles.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileRef(NonZero<RawPolyRef>);
+
+/// The components of a [`PolyRef`], see [`PolyRef::decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodedPolyRef {
+    /// Salt of the tile containing the polygon.
+    pub salt: u32,
+    /// Index of the tile containing the polygon.
+    pub tile: u32,
+    /// Index of the polygon in its tile.
+    pub poly: u32,
+}
+
+impl PolyRef {
+    /// Wrap a raw Detour poly ref. Returns `None` for the null reference `0`.
+    pub fn new(raw: RawPolyRef) -> Option<PolyRef> {
+        NonZero::new(raw).map(PolyRef)
+    }
+
+    /// Return the raw Detour poly ref.
+    pub fn get(self) -> RawPolyRef {
+        self.0.get()
+    }
+
+    pub(crate) fn from_ffi(poly_ref: dtPolyRef) -> Option<PolyRef> {
+        PolyRef::new(poly_ref.0)
+    }
+
+    pub(crate) fn to_ffi(self) -> dtPolyRef {
+        dtPolyRef(self.get())
+    }
+
+    /// Split the reference into its salt, tile index and polygon index. The layout of the
+    /// reference depends on the parameters of the navmesh it belongs to.
+    pub fn decode(self, navmesh: &NavMesh) -> DecodedPolyRef {
+        let lock = navmesh.ptr.read().unwrap();
+        let (mut salt, mut tile, mut poly) = (0, 0, 0);
+        lock.as_ref()
+            .decode_poly_id(self.to_ffi(), &mut salt, &mut tile, &mut poly);
+        DecodedPolyRef { salt, tile, poly }
+    }
+
+    /// Reinterpret a slice of raw poly refs, as passed to the Detour callbacks. Returns `None` if
+    /// the slice contains a null reference.
+    pub(crate) fn from_ffi_slice(raw: &[dtPolyRef]) -> Option<&[PolyRef]> {
+        if raw.contains(&dtPolyRef(0)) {
+            return None;
+        }
+        // Both types are transparent wrappers around a RawPolyRef, which is non-zero here
+        Some(unsafe { std::slice::from_raw_parts(raw.as_ptr() as *const PolyRef, raw.len()) })
+    }
+
+    /// Reinterpret a slice of poly refs as raw Detour poly refs.
+    pub(crate) fn as_ffi_slice(refs: &[PolyRef]) -> &[dtPolyRef] {
+        // Both types are transparent wrappers around a RawPolyRef
+        unsafe { std::slice::from_raw_parts(refs.as_ptr() as *const dtPolyRef, refs.len()) }
+    }
+
+    /// Convert a buffer of raw poly refs filled by Detour, dropping the null references.
+    pub(crate) fn from_ffi_vec(raw: Vec<dtPolyRef>) -> Vec<PolyRef> {
+        raw.into_iter().filter_map(PolyRef::from_ffi).collect()
+    }
+}
+
+impl From<PolyRef> for RawPolyRef {
+    fn from(poly_ref: PolyRef) -> Self {
+        poly_ref.get()
+    }
+}
+
+impl TileRef {
+    /// Wrap a raw Detour tile ref. Returns `None` for the null reference `0`.
+    pub fn new(raw: RawPolyRef) -> Option<TileRef> {
+        NonZero::new(raw).map(TileRef)
+    }
+
+    /// Return the raw Detour tile ref.
+    pub fn get(self) -> RawPolyRef {
+        self.0.get()
+    }
+
+    pub(crate) fn from_ffi(tile_ref: dtTileRef) -> Option<TileRef> {
+        TileRef::new(tile_ref.0)
+    }
+
+    pub(crate) fn to_ffi(self) -> dtTileRef {
+        dtTileRef(self.get())
+    }
+}
+
+impl From<TileRef> for RawPolyRef {
+    fn from(tile_ref: TileRef) -> Self {
+        tile_ref.get()
+    }
+}
+
+impl NavMesh {
+    /// Return whether `poly_ref` refers to an existing polygon of the navmesh.
+    pub fn is_valid_poly_ref(&self, poly_ref: PolyRef) -> bool {
+        let lock = self.ptr.read().unwrap();
+        lock.as_ref().is_valid_poly_ref(poly_ref.to_ffi())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolyRef;
+
+    #[test]
+    fn test_poly_ref_null() {
+        assert_eq!(PolyRef::new(0), None);
+        assert_eq!(PolyRef::new(42).map(PolyRef::get), Some(42));
+        assert_eq!(
+            std::mem::size_of::<Option<PolyRef>>(),
+            std::mem::size_of::<super::RawPolyRef>()
+        );
+    }
+
+    #[test]
+    fn test_poly_ref_width() {
+        let expected = if cfg!(feature = "polyref64") { 8 } else { 4 };
+        assert_eq!(std::mem::size_of::<PolyRef>(), expected);
+    }
+
+    #[test]
+    fn test_poly_ref_slices() {
+        use recast_sys::dtPolyRef;
+
+        let raw = [dtPolyRef(1), dtPolyRef(2), dtPolyRef(3)];
+        let refs = PolyRef::from_ffi_slice(&raw).unwrap();
+        assert_eq!(PolyRef::as_ffi_slice(refs), &raw);
+        assert!(PolyRef::from_ffi_slice(&[dtPolyRef(1), dtPolyRef(0)]).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_poly_ref_decode() {
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let polys = query
+            .query_polygons([0., 0., 0.], [5., 1., 5.], &filter, 64)
+            .unwrap();
+        let mut decoded: Vec<_> = polys.iter().map(|poly| poly.decode(&navmesh)).collect();
+        decoded.sort_by_key(|decoded| decoded.poly);
+        assert!(decoded
+            .iter()
+            .all(|d| d.tile == 0 && d.salt == decoded[0].salt));
+        assert!(decoded.iter().enumerate().all(|(i, d)| d.poly == i as u32));
+
+        assert!(polys.iter().all(|&poly| navmesh.is_valid_poly_ref(poly)));
+        let invalid = PolyRef::new(polys.iter().map(|p| p.get()).max().unwrap() + 1).unwrap();
+        assert!(!navmesh.is_valid_poly_ref(invalid));
+    }
+}
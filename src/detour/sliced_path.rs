@@ -0,0 +1,178 @@
+use recast_sys::ffi::detour::*;
+
+use super::{DetourStatus, Error, NavMeshQueryGuard, PolyRef};
+
+/// Progress of a [`SlicedPathSearch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlicedPathStatus {
+    /// The search needs more iterations to complete.
+    InProgress,
+    /// The search is complete, and the path can be retrieved with
+    /// [`finalize`](SlicedPathSearch::finalize).
+    Done,
+}
+
+/// A path search performed over several calls to [`step`](Self::step), so that its cost can be
+/// spread over multiple frames.
+///
+/// The search state is stored in the navmesh query, which is mutably borrowed for as long as this
+/// object exists, along with the query filter.
+pub struct SlicedPathSearch<'s, 'q> {
+    guard: &'s mut NavMeshQueryGuard<'q>,
+    _filter: &'s dtQueryFilter,
+}
+
+impl<'q> NavMeshQueryGuard<'q> {
+    /// Start a sliced search for a path going from the origin point to the destination.
+    ///
+    /// No search iterations are performed by this method. See [`find_path`](Self::find_path)
+    /// for the requirements on the origin and destination.
+    pub fn init_sliced_find_path<'s, F: AsRef<dtQueryFilter>>(
+        &'s mut self,
+        orig: [f32; 3],
+        orig_poly: PolyRef,
+        dest: [f32; 3],
+        dest_poly: PolyRef,
+        filter: &'s F,
+    ) -> crate::Result<SlicedPathSearch<'s, 'q>> {
+        let filter = filter.as_ref();
+        let status: DetourStatus = unsafe {
+            let _lock = self.ptr.read().unwrap();
+            self.query
+                .pin_mut()
+                .init_sliced_find_path(
+                    orig_poly.to_ffi(),
+                    dest_poly.to_ffi(),
+                    orig.as_ptr(),
+                    dest.as_ptr(),
+                    filter as *const _,
+                    0,
+                )
+                .into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(SlicedPathSearch {
+            guard: self,
+            _filter: filter,
+        })
+    }
+}
+
+impl<'s, 'q> SlicedPathSearch<'s, 'q> {
+    /// Perform up to `max_iters` iterations of the search. Returns the status of the search and
+    /// the number of iterations actually performed.
+    pub fn step(&mut self, max_iters: u32) -> crate::Result<(SlicedPathStatus, u32)> {
+        let _lock = self.guard.ptr.read().unwrap();
+        let mut done_iters = 0;
+        let status: DetourStatus = unsafe {
+            self.guard
+                .query
+                .pin_mut()
+                .update_sliced_find_path(max_iters as i32, &mut done_iters as *mut i32)
+                .into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        let progress = if status.is_in_progress() {
+            SlicedPathStatus::InProgress
+        } else {
+            SlicedPathStatus::Done
+        };
+        Ok((progress, done_iters as u32))
+    }
+
+    /// Return the path found by a completed search, going through a maximum of `max_len`
+    /// polygons.
+    ///
+    /// If the destination could not be reached, the path leads to the polygon closest to it.
+    pub fn finalize(self, max_len: u32) -> crate::Result<Vec<PolyRef>> {
+        self.finalize_with(max_len, |query, path, path_len| unsafe {
+            query.finalize_sliced_find_path(path, path_len, max_len as i32)
+        })
+    }
+
+    /// Return the path found by an incomplete search, going through a maximum of `max_len`
+    /// polygons. The path leads to the furthest polygon of `existing` visited by the search.
+    ///
+    /// This is used to update an existing path when the search is aborted before completion.
+    pub fn finalize_partial(
+        self,
+        existing: &[PolyRef],
+        max_len: u32,
+    ) -> crate::Result<Vec<PolyRef>> {
+        self.finalize_with(max_len, |query, path, path_len| unsafe {
+            query.finalize_sliced_find_path_partial(
+                PolyRef::as_ffi_slice(existing).as_ptr(),
+                existing.len() as i32,
+                path,
+                path_len,
+                max_len as i32,
+            )
+        })
+    }
+
+    fn finalize_with<F>(self, max_len: u32, finalize: F) -> crate::Result<Vec<PolyRef>>
+    where
+        F: FnOnce(std::pin::Pin<&mut dtNavMeshQuery>, *mut dtPolyRef, *mut i32) -> u32,
+    {
+        let _lock = self.guard.ptr.read().unwrap();
+        let mut path_vec = Vec::with_capacity(max_len as usize);
+        let mut path_len = 0;
+        let status: DetourStatus = unsafe {
+            let status = finalize(
+                self.guard.query.pin_mut(),
+                path_vec.as_mut_ptr(),
+                &mut path_len as *mut i32,
+            );
+            path_vec.set_len(path_len as usize);
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(PolyRef::from_ffi_vec(path_vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_sliced_find_path() {
+        use super::SlicedPathStatus;
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let (orig_poly, orig) = query
+            .find_nearest_polygon([-8., 0., -8.], [1., 1., 1.], &filter)
+            .unwrap();
+        let (dest_poly, dest) = query
+            .find_nearest_polygon([8., 0., 8.], [1., 1., 1.], &filter)
+            .unwrap();
+        let path = query
+            .find_path(orig, orig_poly, dest, dest_poly, 64, &filter)
+            .unwrap();
+
+        let mut search = query
+            .init_sliced_find_path(orig, orig_poly, dest, dest_poly, &filter)
+            .unwrap();
+        let mut steps = 0;
+        loop {
+            let (status, iters) = search.step(1).unwrap();
+            assert!(iters <= 1);
+            steps += 1;
+            if status == SlicedPathStatus::Done {
+                break;
+            }
+        }
+        assert!(steps >= path.len());
+        assert_eq!(search.finalize(64).unwrap(), path);
+    }
+}
@@ -0,0 +1,108 @@
+use super::{DetourStatus, Error, NavMesh, TileRef};
+
+impl NavMesh {
+    /// Return a snapshot of the runtime state of a tile, i.e. the flags and area ids of its
+    /// polygons, which can be applied back with [`restore_tile_state`](Self::restore_tile_state).
+    ///
+    /// This is much smaller than the tile data, and is meant to persist the changes made by
+    /// [`set_poly_flags`](Self::set_poly_flags) and [`set_poly_area`](Self::set_poly_area).
+    pub fn store_tile_state(&self, tile: TileRef) -> crate::Result<Vec<u8>> {
+        let lock = self.ptr.read().unwrap();
+        let navmesh = lock.as_ref();
+        let tile = navmesh.get_tile_by_ref(tile.to_ffi());
+        if tile.is_null() {
+            return Err(Error::from(DetourStatus::from(
+                DetourStatus::DT_FAILURE | DetourStatus::DT_INVALID_PARAM,
+            )))?;
+        }
+
+        let size = unsafe { navmesh.get_tile_state_size(tile) };
+        let mut buffer = aligned_buffer(size as usize);
+        let status: DetourStatus =
+            unsafe { navmesh.store_tile_state(tile, buffer.as_mut_ptr() as *mut u8, size) }.into();
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        let data =
+            unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, size as usize) };
+        Ok(data.to_vec())
+    }
+
+    /// Restore the state of a tile from a snapshot taken by
+    /// [`store_tile_state`](Self::store_tile_state).
+    ///
+    /// Fails with a [`DetourStatus`] error if the snapshot was not taken from a tile with the same
+    /// polygons, or is not a snapshot. Like [`set_poly_flags`](Self::set_poly_flags), this method
+    /// waits for the queries running on other threads to complete.
+    pub fn restore_tile_state(&self, tile: TileRef, data: &[u8]) -> crate::Result<()> {
+        let mut buffer = aligned_buffer(data.len());
+        let status: DetourStatus = unsafe {
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr(),
+                buffer.as_mut_ptr() as *mut u8,
+                data.len(),
+            );
+            let mut lock = self.ptr.write().unwrap();
+            recast_sys::ffi::detour::restore_tile_state(
+                lock.pin_mut(),
+                tile.to_ffi(),
+                buffer.as_ptr() as *const u8,
+                data.len() as i32,
+            )
+        }
+        .into();
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(())
+    }
+}
+
+/// Allocate a zeroed buffer of at least `size` bytes, aligned for the `dtTileState` and
+/// `dtPolyState` structures Detour accesses through the snapshot pointer.
+fn aligned_buffer(size: usize) -> Vec<u64> {
+    vec![0; size.div_ceil(std::mem::size_of::<u64>())]
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_store_restore_tile_state() {
+        use crate::detour::test_navmesh;
+
+        let navmesh = test_navmesh();
+
+        let (tile, poly) = {
+            let tiles = navmesh.tiles();
+            let tile = tiles.iter().next().unwrap();
+            (
+                tiles.tile_ref(tile).unwrap(),
+                tiles.poly_ref(tile, 0).unwrap(),
+            )
+        };
+        let flags = navmesh.poly_flags(poly).unwrap();
+        let area = navmesh.poly_area(poly).unwrap();
+
+        let state = navmesh.store_tile_state(tile).unwrap();
+        navmesh.set_poly_flags(poly, flags ^ 0x8).unwrap();
+        navmesh.set_poly_area(poly, area - 1).unwrap();
+        navmesh.restore_tile_state(tile, &state).unwrap();
+        assert_eq!(navmesh.poly_flags(poly).unwrap(), flags);
+        assert_eq!(navmesh.poly_area(poly).unwrap(), area);
+
+        // Snapshots don't need to be aligned, e.g. when read from a larger buffer
+        let mut unaligned = vec![0; state.len() + 1];
+        unaligned[1..].copy_from_slice(&state);
+        navmesh.set_poly_flags(poly, flags ^ 0x8).unwrap();
+        navmesh.restore_tile_state(tile, &unaligned[1..]).unwrap();
+        assert_eq!(navmesh.poly_flags(poly).unwrap(), flags);
+
+        match navmesh.restore_tile_state(tile, &[0; 4]) {
+            Err(crate::Error::Detour(crate::detour::Error::Detour(Some(status)))) => {
+                assert!(status.is_failure());
+            }
+            _ => panic!("Expected a Detour error"),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use recast_sys::ffi::detour::*;
+
+use super::{DetourStatus, Error, NavMeshQueryGuard, PolyRef};
+
+/// Result of the [`NavMeshQueryGuard::find_distance_to_wall`] query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallHit {
+    /// Distance to the nearest wall.
+    pub distance: f32,
+    /// Position of the nearest wall point.
+    pub position: [f32; 3],
+    /// Normal of the nearest wall at the hit position, pointing towards the search center.
+    pub normal: [f32; 3],
+}
+
+/// A segment of the boundary of a navmesh polygon, see
+/// [`NavMeshQueryGuard::poly_wall_segments`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallSegment {
+    /// Start point of the segment.
+    pub start: [f32; 3],
+    /// End point of the segment.
+    pub end: [f32; 3],
+    /// Reference of the polygon on the other side of the segment if it is a portal, or `None`
+    /// if it is a wall.
+    pub neighbour: Option<PolyRef>,
+}
+
+impl<'q> NavMeshQueryGuard<'q> {
+    /// Find the distance from the `center` position, lying in the `start_poly` polygon, to the
+    /// nearest wall within `max_radius`.
+    ///
+    /// The search explores the polygons passing the filter. If no wall is found within
+    /// `max_radius`, the returned distance is `max_radius`.
+    pub fn find_distance_to_wall<F: AsRef<dtQueryFilter>>(
+        &mut self,
+        start_poly: PolyRef,
+        center: [f32; 3],
+        max_radius: f32,
+        filter: &F,
+    ) -> crate::Result<WallHit> {
+        let _lock = self.ptr.read().unwrap();
+        let mut distance = 0.;
+        let mut position = [0.; 3];
+        let mut normal = [0.; 3];
+        let status: DetourStatus = unsafe {
+            self.query
+                .as_ref()
+                .find_distance_to_wall(
+                    start_poly.to_ffi(),
+                    center.as_ptr(),
+                    max_radius,
+                    filter.as_ref() as *const _,
+                    &mut distance as *mut f32,
+                    position.as_mut_ptr(),
+                    normal.as_mut_ptr(),
+                )
+                .into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+        Ok(WallHit {
+            distance,
+            position,
+            normal,
+        })
+    }
+
+    /// Return up to `max_segments` segments of the boundary of the specified polygon.
+    ///
+    /// If `include_portals` is `false`, only the walls are returned. Otherwise, the portals to the
+    /// neighbouring polygons are returned too, along with the references of those polygons.
+    /// Portals to polygons not passing the filter are considered walls.
+    pub fn poly_wall_segments<F: AsRef<dtQueryFilter>>(
+        &self,
+        poly: PolyRef,
+        filter: &F,
+        max_segments: u32,
+        include_portals: bool,
+    ) -> crate::Result<Vec<WallSegment>> {
+        let _lock = self.ptr.read().unwrap();
+        let mut vertices: Vec<f32> = Vec::with_capacity(max_segments as usize * 6);
+        let mut refs: Vec<dtPolyRef> = Vec::with_capacity(max_segments as usize);
+        let mut segment_count = 0;
+        let status: DetourStatus = unsafe {
+            let refs_ptr = if include_portals {
+                refs.as_mut_ptr()
+            } else {
+                std::ptr::null_mut()
+            };
+            let status = self.query.as_ref().get_poly_wall_segments(
+                poly.to_ffi(),
+                filter.as_ref() as *const _,
+                vertices.as_mut_ptr(),
+                refs_ptr,
+                &mut segment_count as *mut i32,
+                max_segments as i32,
+            );
+            vertices.set_len(segment_count as usize * 6);
+            if include_portals {
+                refs.set_len(segment_count as usize);
+            }
+            status.into()
+        };
+        if status.is_failure() {
+            return Err(Error::from(status))?;
+        }
+
+        let segments = vertices
+            .chunks_exact(6)
+            .enumerate()
+            .map(|(i, segment)| WallSegment {
+                start: segment[..3].try_into().unwrap(),
+                end: segment[3..].try_into().unwrap(),
+                neighbour: refs.get(i).copied().and_then(PolyRef::from_ffi),
+            })
+            .collect();
+        Ok(segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "recast")]
+    fn test_wall_queries() {
+        use crate::detour::{test_navmesh, QueryFilter};
+
+        let navmesh = test_navmesh();
+        let mut query = navmesh.new_query(256).unwrap();
+        let mut query = query.upgrade().unwrap();
+        let filter = QueryFilter::default();
+
+        let (poly, center) = query
+            .find_nearest_polygon([8., 0., 0.], [1., 1., 1.], &filter)
+            .unwrap();
+        let hit = query
+            .find_distance_to_wall(poly, center, 5., &filter)
+            .unwrap();
+        assert!(hit.distance > 0. && hit.distance < 2.);
+        assert!(hit.position[0] > center[0]);
+        assert!(hit.normal[0] < 0.);
+
+        let walls = query.poly_wall_segments(poly, &filter, 16, false).unwrap();
+        assert!(!walls.is_empty());
+        assert!(walls.iter().all(|segment| segment.neighbour.is_none()));
+
+        let segments = query.poly_wall_segments(poly, &filter, 16, true).unwrap();
+        assert!(segments.len() >= walls.len());
+        let walls_with_portals = segments.iter().filter(|s| s.neighbour.is_none()).count();
+        assert_eq!(walls_with_portals, walls.len());
+    }
+}
@@ -23,6 +23,8 @@ pub enum Error {
     #[cfg(feature = "detour")]
     #[error("A detour error occurred: {0}")]
     Detour(#[from] DetourError),
+    #[error("An I/O error occurred: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -12,9 +12,13 @@
 /// # fn main() {}
 /// uptr_wrapper!(PolyMeshWrapper, recast_sys::ffi::recast::rcPolyMesh, recast_sys::ffi::recast::new_poly_mesh);
 /// ```
+///
+/// Attributes such as doc comments can be placed before the visibility of the type and are
+/// forwarded to the type definition.
 #[macro_export]
 macro_rules! uptr_wrapper {
-    ($visi:vis $my_t:ident, $rc_t:ty, $rc_ctor:path) => {
+    ($(#[$attr:meta])* $visi:vis $my_t:ident, $rc_t:ty, $rc_ctor:path) => {
+        $(#[$attr])*
         $visi struct $my_t {
             ptr: ::cxx::UniquePtr<$rc_t>
         }
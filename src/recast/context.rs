@@ -2,15 +2,15 @@ use cxx::UniquePtr;
 use recast_sys::{ffi::recast::*, RecastConfig};
 
 #[cfg(feature = "detour")]
-use recast_sys::ffi::detour::*;
-
-#[cfg(feature = "detour")]
-use crate::detour::NavMesh;
+use crate::{
+    detour::{NavMesh, OffMeshConnection},
+    recast::NavMeshBuilder,
+};
 
 use crate::{
     recast::{
         CompactHeightField, ContourSet, HeightField, MarkedMesh, Mesh, PolyMesh, PolyMeshDetail,
-        RecastError, RecastNavMeshData,
+        RecastError, RecastNavMeshData, MAX_AREAS,
     },
     Error,
 };
@@ -20,6 +20,7 @@ pub struct RecastContext {
     config: RecastConfig,
     grid_width: i32,
     grid_height: i32,
+    area_flags: Box<dyn Fn(u8) -> u16>,
 }
 
 impl RecastContext {
@@ -45,6 +46,7 @@ impl RecastContext {
             config,
             grid_width,
             grid_height,
+            area_flags: Box::new(|_area| 1),
         })
     }
 
@@ -55,40 +57,70 @@ impl RecastContext {
         &mut self.config
     }
 
+    /// Set the function mapping polygon area ids to Detour polygon flags.
+    ///
+    /// The flags are what Detour query filters match their include and exclude flags against, so
+    /// this is where areas such as water or doors get translated to flags like "swim" or "door".
+    /// The mapping is applied by [`apply_area_flags`](Self::apply_area_flags), which the
+    /// [`default_pipeline`](Self::default_pipeline) calls right after building the polygon mesh.
+    ///
+    /// By default, every polygon is given the flag `1`.
+    pub fn set_area_flags<F>(&mut self, mapping: F)
+    where
+        F: Fn(u8) -> u16 + 'static,
+    {
+        self.area_flags = Box::new(mapping);
+    }
+
+    /// Set a lookup table mapping polygon area ids to Detour polygon flags, indexed by area id.
+    ///
+    /// See [`set_area_flags`](Self::set_area_flags) for details.
+    pub fn set_area_flags_table(&mut self, table: [u16; MAX_AREAS]) {
+        self.set_area_flags(move |area| table.get(area as usize).copied().unwrap_or(0));
+    }
+
+    /// Set the flags of every polygon of the mesh according to its area id, using the mapping
+    /// configured with [`set_area_flags`](Self::set_area_flags).
+    pub fn apply_area_flags(&self, poly_mesh: &mut PolyMesh) {
+        let areas = poly_mesh.areas().to_vec();
+        for (flags, area) in poly_mesh.flags_mut().iter_mut().zip(areas) {
+            *flags = (self.area_flags)(area);
+        }
+    }
+
     // Not sure how to fix this one
-    /// Mark triangles of the input mesh with a slope below the configured limit as walkable.
+    /// Mark triangles of the input mesh with a slope below the configured limit as walkable,
+    /// giving them the area id of the mesh.
     #[allow(clippy::needless_lifetimes)]
     pub fn mark_walkable_triangles<'ctx, 'data>(
         &'ctx mut self,
         mesh: &Mesh<'data>,
     ) -> MarkedMesh<'data> {
+        let mut marked_mesh = MarkedMesh {
+            vertices: mesh.vertices,
+            indices: mesh.triangle_indices(),
+            areas: vec![mesh.area; mesh.triangle_count()],
+        };
+        self.clear_unwalkable_triangles(&mut marked_mesh);
+        marked_mesh
+    }
+
+    /// Mark triangles of the input mesh with a slope above the configured limit as unwalkable,
+    /// leaving the area id of the other triangles untouched.
+    pub fn clear_unwalkable_triangles(&mut self, mesh: &mut MarkedMesh) {
         let n_vertices = mesh.vertices.len() / 3;
-        let indices = mesh.indices.map(|s| s.to_owned()).unwrap_or_else(|| {
-            mesh.vertices
-                .chunks_exact(3)
-                .enumerate()
-                .map(|(i, _v)| i as i32)
-                .collect::<Vec<_>>()
-        });
-        let n_triangles = &indices.len() / 3;
-
-        let mut walkable_areas = vec![0; n_triangles];
+        let n_triangles = mesh.indices.len() / 3;
         unsafe {
-            recast_sys::ffi::recast::mark_walkable_triangles(
+            recast_sys::ffi::recast::clear_unwalkable_triangles(
                 self.context_ptr(),
                 self.config.walkable_slope_angle,
                 mesh.vertices.as_ptr(),
                 n_vertices as i32,
-                indices.as_ptr(),
+                mesh.indices.as_ptr(),
                 n_triangles as i32,
-                walkable_areas.as_mut_ptr(),
+                mesh.areas.as_mut_ptr(),
             )
         };
-        MarkedMesh {
-            vertices: mesh.vertices,
-            indices,
-            areas: walkable_areas,
-        }
     }
 
     /// Construct a new `HeightField` of specified dimensions.
@@ -151,10 +183,7 @@ impl RecastContext {
 
         let mut poly_mesh = PolyMesh::new().unwrap();
         self.build_poly_mesh(&mut contour_set, &mut poly_mesh);
-        // TODO: this should not be arcane as it is. this is about the flags thingy for detour filter queries, btw
-        for flag in poly_mesh.flags_mut() {
-            *flag = 1;
-        }
+        self.apply_area_flags(&mut poly_mesh);
 
         let mut detail = PolyMeshDetail::new().unwrap();
         self.build_poly_mesh_detail(&poly_mesh, &compact_heightfield, &mut detail);
@@ -168,21 +197,28 @@ impl RecastContext {
         &mut self,
         input_geo: I,
     ) -> Result<(RecastNavMeshData, NavMesh), Error>
+    where
+        I: IntoIterator<Item = &'a Mesh<'a>>,
+    {
+        self.default_pipeline_detour_with_connections(input_geo, &[])
+    }
+
+    /// Run the `default_pipeline` and build a Detour navmesh from the Recast results and the
+    /// specified off-mesh connections.
+    #[cfg(feature = "detour")]
+    pub fn default_pipeline_detour_with_connections<'a, I>(
+        &mut self,
+        input_geo: I,
+        off_mesh_connections: &[OffMeshConnection],
+    ) -> Result<(RecastNavMeshData, NavMesh), Error>
     where
         I: IntoIterator<Item = &'a Mesh<'a>>,
     {
         let navmesh_data = self.default_pipeline(input_geo)?;
-        let mut create_mesh_data = NavMeshCreateParams::from(&navmesh_data);
-        create_mesh_data.b_min = self.config.bmin;
-        create_mesh_data.b_max = self.config.bmax;
-        create_mesh_data.walkable_height = self.config.walkable_height as f32 * self.config.ch;
-        create_mesh_data.walkable_radius = self.config.walkable_radius as f32 * self.config.cs;
-        create_mesh_data.walkable_climb = self.config.walkable_climb as f32 * self.config.ch;
-        create_mesh_data.cs = self.config.cs;
-        create_mesh_data.ch = self.config.ch;
-        create_mesh_data.build_bv_tree = true;
-
-        let navmesh = NavMesh::single_tile(create_mesh_data)?;
+        let navmesh = NavMeshBuilder::from(&navmesh_data)
+            .with_config(&self.config)
+            .with_off_mesh_connections(off_mesh_connections)?
+            .build()?;
 
         Ok((navmesh_data, navmesh))
     }
@@ -343,7 +379,7 @@ impl RecastContext {
 mod tests {
     use recast_sys::RecastConfig;
 
-    use crate::recast::Mesh;
+    use crate::recast::{MarkedMesh, Mesh, NULL_AREA, WALKABLE_AREA};
 
     use super::RecastContext;
 
@@ -408,6 +444,95 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    #[test]
+    fn default_pipeline_applies_area_flags() {
+        let mut context = RecastContext::new(RecastConfig {
+            bmin: SAMPLE_TRI_MESH_BMIN,
+            bmax: SAMPLE_TRI_MESH_BMAX,
+            ..Default::default()
+        })
+        .unwrap();
+        context.set_area_flags(|area| if area == WALKABLE_AREA { 0x4 } else { 0 });
+        let buf = SAMPLE_TRI_MESH
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let mesh = Mesh::from_vertex_buffer(buf.as_slice()).unwrap();
+        let data = context.default_pipeline(&[mesh]).unwrap();
+        assert!(!data.poly_mesh.flags().is_empty());
+        assert!(data.poly_mesh.flags().iter().all(|&flags| flags == 0x4));
+    }
+
+    #[test]
+    fn default_pipeline_uses_mesh_area() {
+        let mut context = RecastContext::new(RecastConfig {
+            bmin: SAMPLE_TRI_MESH_BMIN,
+            bmax: SAMPLE_TRI_MESH_BMAX,
+            ..Default::default()
+        })
+        .unwrap();
+        let buf = SAMPLE_TRI_MESH
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let mesh = Mesh::from_buffers(buf.as_slice(), SAMPLE_TRI_MESH_INDICES)
+            .unwrap()
+            .with_area(5);
+        let data = context.default_pipeline(&[mesh]).unwrap();
+        assert!(!data.poly_mesh.areas().is_empty());
+        assert!(data.poly_mesh.areas().iter().all(|&area| area == 5));
+    }
+
+    #[test]
+    fn clear_unwalkable_triangles_keeps_flat_triangles() {
+        let mut context = RecastContext::new(RecastConfig::default()).unwrap();
+        let buf = SAMPLE_TRI_MESH
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let mesh = Mesh::from_buffers(buf.as_slice(), SAMPLE_TRI_MESH_INDICES).unwrap();
+        let mut marked =
+            MarkedMesh::from_fn(&mesh, |i, _| if i == 0 { 7 } else { NULL_AREA }).unwrap();
+        context.clear_unwalkable_triangles(&mut marked);
+        assert_eq!(marked.areas(), &[7, NULL_AREA]);
+        assert!(MarkedMesh::new(&mesh, vec![WALKABLE_AREA]).is_err());
+    }
+
+    #[test]
+    fn marked_mesh_rejects_invalid_areas() {
+        let buf = SAMPLE_TRI_MESH
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let mesh = Mesh::from_buffers(buf.as_slice(), SAMPLE_TRI_MESH_INDICES).unwrap();
+        assert!(MarkedMesh::new(&mesh, vec![WALKABLE_AREA, WALKABLE_AREA]).is_ok());
+        assert!(MarkedMesh::new(&mesh, vec![WALKABLE_AREA, WALKABLE_AREA + 1]).is_err());
+        assert!(MarkedMesh::from_fn(&mesh, |_, _| WALKABLE_AREA + 1).is_err());
+
+        let mut marked = MarkedMesh::from_fn(&mesh, |_, _| WALKABLE_AREA).unwrap();
+        assert!(marked.set_area(1, 5).is_ok());
+        assert!(marked.set_area(1, WALKABLE_AREA + 1).is_err());
+        assert!(marked.set_area(2, 5).is_err());
+        assert_eq!(marked.areas(), &[WALKABLE_AREA, 5]);
+    }
+
+    #[test]
+    fn marked_mesh_rejects_invalid_indices() {
+        let buf = SAMPLE_TRI_MESH
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let indices = [0, 1, 2, 2, 3, 4];
+        let mesh = Mesh::from_buffers(buf.as_slice(), &indices).unwrap();
+        assert!(MarkedMesh::new(&mesh, vec![WALKABLE_AREA, WALKABLE_AREA]).is_err());
+        assert!(MarkedMesh::from_fn(&mesh, |_, _| WALKABLE_AREA).is_err());
+    }
+
     #[test]
     #[cfg(feature = "detour")]
     fn default_detour_pipeline_sample_mesh_succeeds() {
@@ -427,4 +552,52 @@ mod tests {
         let res = context.default_pipeline_detour(&[mesh]);
         assert!(res.is_ok());
     }
+
+    #[test]
+    #[cfg(feature = "detour")]
+    fn default_detour_pipeline_with_off_mesh_connections_succeeds() {
+        use crate::detour::{OffMeshConnection, PolyType};
+
+        let mut context = RecastContext::new(RecastConfig {
+            bmin: SAMPLE_TRI_MESH_BMIN,
+            bmax: SAMPLE_TRI_MESH_BMAX,
+            ..Default::default()
+        })
+        .unwrap();
+        let buf = SAMPLE_TRI_MESH
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let mesh = Mesh::from_vertex_buffer(buf.as_slice()).unwrap();
+        let connection = OffMeshConnection {
+            start: [-5., 0., -5.],
+            end: [5., 0., 5.],
+            radius: 0.5,
+            bidirectional: true,
+            area: WALKABLE_AREA,
+            flags: 1,
+            user_id: 1,
+        };
+        let invalid_connection = OffMeshConnection {
+            area: 64,
+            ..connection.clone()
+        };
+        let res = context.default_pipeline_detour_with_connections([&mesh], &[invalid_connection]);
+        assert!(res.is_err());
+        let (_, navmesh) = context
+            .default_pipeline_detour_with_connections([&mesh], std::slice::from_ref(&connection))
+            .unwrap();
+
+        let tiles = navmesh.tiles();
+        let tile = tiles.iter().next().unwrap();
+        let poly = tile
+            .polys()
+            .find(|poly| poly.poly_type() == PolyType::OffMeshConnection)
+            .unwrap();
+        assert_eq!(poly.area(), WALKABLE_AREA);
+        assert_eq!(poly.flags(), 1);
+        let endpoints: Vec<_> = tile.poly_vertices(poly).collect();
+        assert_eq!(endpoints, [connection.start, connection.end]);
+    }
 }
@@ -1,7 +1,10 @@
+use super::WALKABLE_AREA;
+
 #[derive(Debug)]
 pub struct Mesh<'a> {
     pub(crate) vertices: &'a [f32],
-    pub(crate) indices: Option<&'a [i32]>
+    pub(crate) indices: Option<&'a [i32]>,
+    pub(crate) area: u8,
 }
 
 impl<'a> Mesh<'a> {
@@ -12,7 +15,7 @@ impl<'a> Mesh<'a> {
             return Err(format!("The maximum support side for vertices and indices buffers is {}, split the mesh in smaller pieces.", std::i32::MAX));
         }
 
-        Ok(Mesh { vertices, indices: None })
+        Ok(Mesh { vertices, indices: None, area: WALKABLE_AREA })
     }
 
     pub fn from_buffers(vertices: &'a [f32], indices: &'a [i32]) -> Result<Mesh<'a>, String> {
@@ -20,13 +23,121 @@ impl<'a> Mesh<'a> {
             return Err(format!("The maximum support side for vertices and indices buffers is {}, split the mesh in smaller pieces.", std::i32::MAX));
         }
 
-        Ok(Mesh { vertices, indices: Some(indices) })
+        Ok(Mesh { vertices, indices: Some(indices), area: WALKABLE_AREA })
+    }
+
+    /// Set the area id given to the walkable triangles of this mesh when it is marked by
+    /// [`RecastContext::mark_walkable_triangles`](super::RecastContext::mark_walkable_triangles).
+    /// Defaults to [`WALKABLE_AREA`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `area` is not a valid area id, i.e. if it is greater than [`WALKABLE_AREA`].
+    pub fn with_area(mut self, area: u8) -> Mesh<'a> {
+        assert!(area <= WALKABLE_AREA, "Invalid area id {}", area);
+        self.area = area;
+        self
+    }
+
+    /// Return the area id given to the walkable triangles of this mesh.
+    pub fn area(&self) -> u8 {
+        self.area
+    }
+
+    /// Return the number of triangles in this mesh.
+    pub fn triangle_count(&self) -> usize {
+        match self.indices {
+            Some(indices) => indices.len() / 3,
+            None => self.vertices.len() / 9,
+        }
+    }
+
+    /// Return the index buffer of the mesh, generating one if the mesh is not indexed.
+    pub(crate) fn triangle_indices(&self) -> Vec<i32> {
+        self.indices.map(|s| s.to_owned()).unwrap_or_else(|| {
+            (0..(self.vertices.len() / 3) as i32).collect()
+        })
     }
 }
 
+/// A triangle mesh with an area id attached to each of its triangles, ready to be rasterized.
 #[derive(Debug)]
 pub struct MarkedMesh<'a> {
     pub(crate) vertices: &'a [f32],
     pub(crate) indices: Vec<i32>,
     pub(crate) areas: Vec<u8>
 }
+
+impl<'a> MarkedMesh<'a> {
+    /// Attach the specified area ids to the triangles of a mesh, one area id per triangle.
+    ///
+    /// Fails if an area id is greater than [`WALKABLE_AREA`], or if the mesh indexes vertices it
+    /// does not have.
+    pub fn new(mesh: &Mesh<'a>, areas: Vec<u8>) -> Result<MarkedMesh<'a>, String> {
+        if areas.len() != mesh.triangle_count() {
+            return Err(format!("Expected {} triangle areas, got {}.", mesh.triangle_count(), areas.len()));
+        }
+        if let Some(area) = areas.iter().find(|&&area| area > WALKABLE_AREA) {
+            return Err(format!("Invalid area id {}.", area));
+        }
+
+        Ok(MarkedMesh { vertices: mesh.vertices, indices: Self::checked_indices(mesh)?, areas })
+    }
+
+    /// Mark the triangles of a mesh using a classification function, which is passed the index
+    /// and the vertices of every triangle and returns its area id.
+    ///
+    /// Fails if `classify` returns an area id greater than [`WALKABLE_AREA`], or if the mesh
+    /// indexes vertices it does not have.
+    pub fn from_fn<F>(mesh: &Mesh<'a>, mut classify: F) -> Result<MarkedMesh<'a>, String>
+    where
+        F: FnMut(usize, [[f32; 3]; 3]) -> u8,
+    {
+        let indices = Self::checked_indices(mesh)?;
+        let vertex = |i: i32| -> [f32; 3] {
+            let i = i as usize * 3;
+            mesh.vertices[i..i + 3].try_into().unwrap()
+        };
+        let mut areas = Vec::with_capacity(indices.len() / 3);
+        for (i, tri) in indices.chunks_exact(3).enumerate() {
+            let area = classify(i, [vertex(tri[0]), vertex(tri[1]), vertex(tri[2])]);
+            if area > WALKABLE_AREA {
+                return Err(format!("Invalid area id {}.", area));
+            }
+            areas.push(area);
+        }
+
+        Ok(MarkedMesh { vertices: mesh.vertices, indices, areas })
+    }
+
+    /// Return the area ids of the triangles of the mesh.
+    pub fn areas(&self) -> &[u8] {
+        &self.areas
+    }
+
+    /// Set the area id of the triangle at index `triangle`.
+    ///
+    /// Fails if the triangle does not exist or if `area` is greater than [`WALKABLE_AREA`].
+    pub fn set_area(&mut self, triangle: usize, area: u8) -> Result<(), String> {
+        if area > WALKABLE_AREA {
+            return Err(format!("Invalid area id {}.", area));
+        }
+        match self.areas.get_mut(triangle) {
+            Some(triangle_area) => {
+                *triangle_area = area;
+                Ok(())
+            }
+            None => Err(format!("Invalid triangle index {}.", triangle)),
+        }
+    }
+
+    /// Return the index buffer of the mesh, checking that all the indices refer to a vertex.
+    fn checked_indices(mesh: &Mesh) -> Result<Vec<i32>, String> {
+        let indices = mesh.triangle_indices();
+        let n_vertices = mesh.vertices.len() / 3;
+        if let Some(index) = indices.iter().find(|&&i| i < 0 || i as usize >= n_vertices) {
+            return Err(format!("Invalid vertex index {}.", index));
+        }
+        Ok(indices)
+    }
+}
@@ -5,18 +5,29 @@ use thiserror::Error;
 
 mod context;
 mod mesh;
+#[cfg(feature = "detour")]
+mod navmesh_builder;
 mod navmesh_data;
 mod poly_mesh;
 mod poly_mesh_detail;
 
 pub use context::*;
 pub use mesh::*;
+#[cfg(feature = "detour")]
+pub use navmesh_builder::*;
 pub use navmesh_data::*;
 pub use poly_mesh::*;
 pub use poly_mesh_detail::*;
 
 pub use recast_sys::RecastConfig;
 
+/// Area id given by Recast to walkable triangles and spans (`RC_WALKABLE_AREA`).
+pub const WALKABLE_AREA: u8 = 63;
+/// Area id of unwalkable triangles and spans (`RC_NULL_AREA`).
+pub const NULL_AREA: u8 = 0;
+/// Number of distinct area ids. Recast and Detour store area ids on 6 bits.
+pub const MAX_AREAS: usize = 64;
+
 #[derive(Debug, Error)]
 pub enum RecastError {
     #[error("Failed to allocate memory")]
@@ -48,3 +59,19 @@ uptr_wrapper!(
 uptr_wrapper!(pub ContourSet, rcContourSet, new_contour_set);
 uptr_wrapper!(pub PolyMesh, rcPolyMesh, new_poly_mesh);
 uptr_wrapper!(pub PolyMeshDetail, rcPolyMeshDetailOwned, new_poly_mesh_detail_owned);
+
+/// Build the context and mesh shared by the tests: a 20x20 quad centered on the origin, within
+/// 30x30 bounds.
+#[cfg(test)]
+pub(crate) fn test_quad() -> (RecastContext, Mesh<'static>) {
+    const VERTICES: &[f32] = &[
+        -10., 0., 10., 10., 0., 10., 10., 0., -10., -10., 0., 10., 10., 0., -10., -10., 0., -10.,
+    ];
+    let context = RecastContext::new(RecastConfig {
+        bmin: [-15., -1., -15.],
+        bmax: [15., 1., 15.],
+        ..Default::default()
+    })
+    .unwrap();
+    (context, Mesh::from_vertex_buffer(VERTICES).unwrap())
+}
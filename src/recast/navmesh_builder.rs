@@ -0,0 +1,171 @@
+use recast_sys::{ffi::detour::NavMeshCreateParams, RecastConfig};
+
+use crate::{
+    detour::{NavMesh, OffMeshConnection, OffMeshConnectionBuffers},
+    recast::{PolyMesh, PolyMeshDetail, RecastNavMeshData},
+};
+
+/// Builds a single-tile Detour [`NavMesh`] out of the output of the Recast pipeline.
+///
+/// The builder borrows the Recast data and owns any additional buffer, which guarantees the data
+/// Detour reads from remains valid while the navmesh is created.
+pub struct NavMeshBuilder<'a> {
+    poly_mesh: &'a PolyMesh,
+    detail: Option<&'a PolyMeshDetail>,
+    off_mesh_connections: OffMeshConnectionBuffers,
+    user_id: u32,
+    tile_x: i32,
+    tile_y: i32,
+    tile_layer: i32,
+    b_min: [f32; 3],
+    b_max: [f32; 3],
+    walkable_height: f32,
+    walkable_radius: f32,
+    walkable_climb: f32,
+    cs: f32,
+    ch: f32,
+    build_bv_tree: bool,
+}
+
+impl<'a> NavMeshBuilder<'a> {
+    /// Create a builder for a navmesh made of the polygons of `poly_mesh`.
+    ///
+    /// The bounds, cell sizes and agent dimensions default to zero and should be set with
+    /// [`with_config`](Self::with_config).
+    pub fn new(poly_mesh: &'a PolyMesh) -> NavMeshBuilder<'a> {
+        NavMeshBuilder {
+            poly_mesh,
+            detail: None,
+            off_mesh_connections: OffMeshConnectionBuffers::default(),
+            user_id: 0,
+            tile_x: 0,
+            tile_y: 0,
+            tile_layer: 0,
+            b_min: [0.; 3],
+            b_max: [0.; 3],
+            walkable_height: 0.,
+            walkable_radius: 0.,
+            walkable_climb: 0.,
+            cs: 0.,
+            ch: 0.,
+            build_bv_tree: true,
+        }
+    }
+
+    /// Use the height details of `detail` for the navmesh polygons.
+    pub fn with_detail(mut self, detail: &'a PolyMeshDetail) -> NavMeshBuilder<'a> {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// Set the bounds, cell sizes and agent dimensions of the navmesh from the configuration
+    /// used to build the Recast data.
+    pub fn with_config(mut self, config: &RecastConfig) -> NavMeshBuilder<'a> {
+        self.b_min = config.bmin;
+        self.b_max = config.bmax;
+        self.walkable_height = config.walkable_height as f32 * config.ch;
+        self.walkable_radius = config.walkable_radius as f32 * config.cs;
+        self.walkable_climb = config.walkable_climb as f32 * config.ch;
+        self.cs = config.cs;
+        self.ch = config.ch;
+        self
+    }
+
+    /// Add the specified off-mesh connections to the navmesh.
+    ///
+    /// Fails with a [`DetourStatus`](crate::detour::DetourStatus) error if the area id of a
+    /// connection is greater than 63.
+    pub fn with_off_mesh_connections(
+        mut self,
+        connections: &[OffMeshConnection],
+    ) -> crate::Result<NavMeshBuilder<'a>> {
+        self.off_mesh_connections = OffMeshConnectionBuffers::new(connections)?;
+        Ok(self)
+    }
+
+    /// Set the location of the tile in the tile grid of the navmesh.
+    pub fn with_tile(mut self, x: i32, y: i32, layer: i32) -> NavMeshBuilder<'a> {
+        self.tile_x = x;
+        self.tile_y = y;
+        self.tile_layer = layer;
+        self
+    }
+
+    /// Set the user defined identifier of the tile.
+    pub fn with_user_id(mut self, user_id: u32) -> NavMeshBuilder<'a> {
+        self.user_id = user_id;
+        self
+    }
+
+    /// Set whether a bounding volume tree should be built for the tile. Enabled by default.
+    pub fn with_bv_tree(mut self, build_bv_tree: bool) -> NavMeshBuilder<'a> {
+        self.build_bv_tree = build_bv_tree;
+        self
+    }
+
+    /// Create the navmesh.
+    pub fn build(&self) -> crate::Result<NavMesh> {
+        // The parameters point into data borrowed or owned by `self`, which outlives this call.
+        unsafe { NavMesh::single_tile(self.create_params()) }
+    }
+
+    fn create_params(&self) -> NavMeshCreateParams {
+        let mut params = NavMeshCreateParams::from(self.poly_mesh);
+        if let Some(detail) = self.detail {
+            params.detail_meshes =
+                recast_sys::ffi::recast::poly_mesh_detail_meshes(detail.as_ref());
+            params.detail_vertices =
+                recast_sys::ffi::recast::poly_mesh_detail_vertices(detail.as_ref());
+            params.num_detail_vertices =
+                recast_sys::ffi::recast::poly_mesh_detail_num_vertices(detail.as_ref());
+            params.detail_triangles =
+                recast_sys::ffi::recast::poly_mesh_detail_triangles(detail.as_ref());
+            params.num_detail_triangles =
+                recast_sys::ffi::recast::poly_mesh_detail_num_triangles(detail.as_ref());
+        }
+        self.off_mesh_connections.fill_params(&mut params);
+        params.user_id = self.user_id;
+        params.tile_x = self.tile_x;
+        params.tile_y = self.tile_y;
+        params.tile_layer = self.tile_layer;
+        params.b_min = self.b_min;
+        params.b_max = self.b_max;
+        params.walkable_height = self.walkable_height;
+        params.walkable_radius = self.walkable_radius;
+        params.walkable_climb = self.walkable_climb;
+        params.cs = self.cs;
+        params.ch = self.ch;
+        params.build_bv_tree = self.build_bv_tree;
+        params
+    }
+}
+
+impl<'a> From<&'a RecastNavMeshData> for NavMeshBuilder<'a> {
+    fn from(data: &'a RecastNavMeshData) -> NavMeshBuilder<'a> {
+        NavMeshBuilder::new(&data.poly_mesh).with_detail(&data.detail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::recast::{test_quad, NavMeshBuilder, PolyMesh};
+
+    #[test]
+    fn test_build_empty_poly_mesh_fails() {
+        let poly_mesh = PolyMesh::new().unwrap();
+        assert!(NavMeshBuilder::new(&poly_mesh).build().is_err());
+    }
+
+    #[test]
+    fn test_build_from_pipeline_data() {
+        let (mut context, mesh) = test_quad();
+        let config = context.config().clone();
+        let data = context.default_pipeline(&[mesh]).unwrap();
+        let navmesh = NavMeshBuilder::from(&data)
+            .with_config(&config)
+            .with_user_id(12)
+            .build()
+            .unwrap();
+        assert_eq!(navmesh.params().origin, config.bmin);
+    }
+}
@@ -5,6 +5,9 @@ pub struct RecastNavMeshData {
     pub detail: PolyMeshDetail
 }
 
+/// The returned parameters point into the buffers of `data`, and are only valid as long as `data`
+/// is neither dropped nor modified. Prefer using a [`NavMeshBuilder`](super::NavMeshBuilder) to
+/// create navmeshes, which enforces this.
 #[cfg(feature = "detour")]
 impl From<&RecastNavMeshData> for recast_sys::ffi::detour::NavMeshCreateParams {
     fn from(data: &RecastNavMeshData) -> recast_sys::ffi::detour::NavMeshCreateParams {
@@ -46,6 +46,9 @@ impl PolyMesh {
     }
 }
 
+/// The returned parameters point into the buffers of `mesh`, and are only valid as long as `mesh`
+/// is neither dropped nor modified. Prefer using a [`NavMeshBuilder`](super::NavMeshBuilder) to
+/// create navmeshes, which enforces this.
 #[cfg(feature = "detour")]
 impl From<&PolyMesh> for NavMeshCreateParams {
     fn from(mesh: &PolyMesh) -> Self {